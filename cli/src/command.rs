@@ -1,15 +1,17 @@
-use std::fs::File;
-use std::io::{stdout, Write};
-use std::path::PathBuf;
+use std::fs::{remove_file, rename, File};
+use std::io::{stdin, stdout, BufWriter, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::from_utf8;
 use std::sync::mpsc::{channel, Receiver};
 use std::thread::spawn;
 
 use crate::*;
 
-use config::{CreateCommands, TimestampCommands, UsernameCommands};
+use config::{CreateCommands, DeriveCommands, EncodingSettings, EntropyOptions, HashCosts, IdCommands, KeySources, ListCommands, LiteralArgs, OtpCommands, SaltOptions, TimestampCommands, UsernameCommands, VerifyCommands};
 use delegate::{create_serial, create_parallel};
-use rand::thread_rng;
-use lib::{load::*, generators::*, analyze::analyze, visualize::visualize, time::*};
+use log::{info, warn};
+use rand::rngs::OsRng;
+use lib::{load::*, wordlist::*, generators::*, analyze::{analyze, analyze_pem, Report}, visualize::visualize, time::*, random::RandomSource, entropy::*, hash::*, encrypt::*, shamir::*, mac::*, encoding::*, literal::*, speech::*};
 
 type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
 type UnitResult<'a> = Result<(), BoxedError<'a>>;
@@ -31,7 +33,7 @@ fn write_to_stdout(receiver: Receiver<Vec<u8>>, total: usize) -> UnitResult<'sta
     for mut message in receiver {
         counter += 1;
 
-        while message.len() > 0 {
+        while !message.is_empty() {
             // The number of bytes which should be copied to the buffer.
             let length = message.len().min(STDOUT_BUFFER_SIZE);
 
@@ -65,8 +67,113 @@ fn write_to_stdout(receiver: Receiver<Vec<u8>>, total: usize) -> UnitResult<'sta
     Ok(())
 }
 
-pub fn handle_create_command(command: CreateCommands) -> UnitResult<'static> {
+/**
+ * Work out the length of each item, which is the shortest which provides the number of bits when one is given.
+ */
+fn resolve_length<'a>(length: Option<usize>, bits: Option<usize>, entropy: impl Fn(usize) -> f64) -> Result<usize, BoxedError<'a>> {
+    match bits {
+        Some(bits) => match shortest_length(bits, entropy) {
+            Some(value) => Ok(value),
+            None => Err(format!("No length can provide {bits} bits of entropy").into())
+        },
+        None => Ok(length.unwrap())
+    }
+}
+
+/**
+ * Report the entropy of each item if requested, and refuse to generate secrets with less than the minimum.
+ */
+fn check_entropy<'a>(options: &EntropyOptions, noun: &str, entropy: f64, secret: bool) -> UnitResult<'a> {
+    if options.report { info!("Entropy: {entropy:.2} bits per {noun}"); }
+
+    if secret && entropy < options.min_bits as f64 {
+        return Err(format!(
+            "Each {noun} would only have {entropy:.2} bits of entropy, which is below the minimum of {} bits. \
+            Increase the length, use --bits or lower --min-bits",
+            options.min_bits
+        ).into());
+    }
+
+    Ok(())
+}
+
+/**
+ * Generate bytes as an array literal when one was asked for, from the same stream the bytes would otherwise use.
+ */
+fn generate_literal<'a>(literal: &LiteralArgs, length: usize, source: &RandomSource) -> Result<Option<Vec<u8>>, BoxedError<'a>> {
+    match literal.language {
+        Some(language) => Ok(Some(format_literal(&generate_bytes(length, &mut source.stream(0)), language, &literal.into())?.into_bytes())),
+        None => Ok(None)
+    }
+}
+
+/**
+ * Load the canonical wordlist if the words are to be read aloud with it, since it's the only speech which needs one.
+ */
+fn load_speech_wordlist<'a>(speak: Option<Speech>) -> Result<Vec<String>, BoxedError<'a>> {
+    match speak {
+        Some(Speech::Words) => load_canonical_wordlist(),
+        _ => Ok(Vec::new())
+    }
+}
+
+/**
+ * Generate bytes as words to be read aloud when asked, from the same stream the bytes would otherwise use.
+ */
+fn generate_speech<'a>(speak: Option<Speech>, length: usize, source: &RandomSource) -> Result<Option<Vec<u8>>, BoxedError<'a>> {
+    match speak {
+        Some(speech) => Ok(Some(speak_bytes(&generate_bytes(length, &mut source.stream(0)), speech, &load_speech_wordlist(speak)?)?.into_bytes())),
+        None => Ok(None)
+    }
+}
+
+/**
+ * Write a generated item as words to be read aloud when asked, once its characters are known to be speakable.
+ */
+fn speak_item(item: Vec<u8>, speak: Option<Speech>, wordlist: &[String]) -> Vec<u8> {
+    match speak {
+        Some(speech) => speak_text(from_utf8(&item).unwrap(), speech, wordlist).unwrap().into_bytes(),
+        None => item
+    }
+}
+
+/**
+ * Create a file which will contain a secret, so that only its owner may read it.
+ *
+ * The permissions are set as the file is created, so it is never readable by anyone else, and an existing file is
+ * never reused, since it may belong to someone else who already holds it open.
+ */
+fn create_private_file<'a>(path: &Path) -> Result<File, BoxedError<'a>> {
+    let mut options = File::options();
+
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        options.mode(0o600);
+    }
+
+    match options.open(path) {
+        Ok(file) => Ok(file),
+        Err(error) if error.kind() == ErrorKind::AlreadyExists => Err(format!("{} already exists, so it was not overwritten", path.display()).into()),
+        Err(error) => Err(error.into())
+    }
+}
+
+/**
+ * Write a file which contains a secret, so that only its owner may read it.
+ */
+fn write_private_file<'a>(path: &Path, contents: &[u8]) -> UnitResult<'a> {
+    create_private_file(path)?.write_all(contents)?;
+
+    Ok(())
+}
+
+pub fn handle_create_command(command: CreateCommands, options: &EntropyOptions, seed: Option<u64>) -> UnitResult<'static> {
     let (sender, receiver) = channel::<Vec<u8>>();
+    let source = RandomSource::new(seed);
     let total: usize;
 
     let handle = match command {
@@ -82,57 +189,444 @@ pub fn handle_create_command(command: CreateCommands) -> UnitResult<'static> {
                 spawn(move || create_serial(sender, || create_timestamp_local(format)))
             }
         },
-        CreateCommands::Bytes { length } => {
+        CreateCommands::Id { command } => match command {
+            IdCommands::Uuid4 { count } => {
+                total = count.unwrap_or(1);
+
+                spawn(move || create_parallel(sender, count, &source, |_, rng| generate_uuid_v4(rng)))
+            },
+            IdCommands::Uuid7 { count } => {
+                total = count.unwrap_or(1);
+
+                let sequence = IdSequence::uuid_v7(&mut source.setup());
+
+                spawn(move || create_parallel(sender, count, &source, |index, rng| generate_uuid_v7(&sequence, index, rng)))
+            },
+            IdCommands::Ulid { count } => {
+                total = count.unwrap_or(1);
+
+                let sequence = IdSequence::ulid(&mut source.setup());
+
+                spawn(move || create_parallel(sender, count, &source, |index, rng| generate_ulid(&sequence, index, rng)))
+            }
+        },
+        CreateCommands::Bytes { encoding, settings, literal, speak, bits, length } => {
+            total = 1;
+
+            let length = resolve_length(length, bits, |length| uniform_bits(256, length))?;
+
+            check_entropy(options, "byte string", uniform_bits(256, length), true)?;
+
+            match (encoding, generate_literal(&literal, length, &source)?.or(generate_speech(speak, length, &source)?)) {
+                (_, Some(output)) => spawn(move || create_serial(sender, || output)),
+                (Some(encoding), None) => {
+                    let encoded = generate_encoded(encoding, &settings.into(), length, &mut source.stream(0))?;
+
+                    spawn(move || create_serial(sender, || encoded))
+                },
+                (None, None) => spawn(move || create_serial(sender, || generate_bytes(length, &mut source.stream(0))))
+            }
+        },
+        CreateCommands::Hex { uppercase, literal, speak, bits, length } => {
+            total = 1;
+
+            let length = resolve_length(length, bits, |length| uniform_bits(256, length))?;
+
+            check_entropy(options, "byte string", uniform_bits(256, length), true)?;
+
+            match generate_literal(&literal, length, &source)?.or(generate_speech(speak, length, &source)?) {
+                Some(output) => spawn(move || create_serial(sender, || output)),
+                None => spawn(move || create_serial(sender, || generate_hex(uppercase, length, &mut source.stream(0))))
+            }
+        },
+        CreateCommands::Base64 { url_safe, literal, bits, length } => {
             total = 1;
 
-            spawn(move || create_serial(sender, || generate_bytes(length)))
+            let length = resolve_length(length, bits, |length| uniform_bits(256, length))?;
+
+            check_entropy(options, "byte string", uniform_bits(256, length), true)?;
+
+            match generate_literal(&literal, length, &source)? {
+                Some(output) => spawn(move || create_serial(sender, || output)),
+                None => spawn(move || create_serial(sender, || generate_base64(url_safe, length, &mut source.stream(0))))
+            }
+        },
+        CreateCommands::Password { numbers, symbols, composition, speak, bits, bits_count, length, count } => {
+            let count = count.or(bits_count);
+
+            let policy = PasswordPolicy {
+                no_digits: !numbers,
+                no_symbols: !symbols,
+                minimums: [composition.min_upper, composition.min_lower, composition.min_digits, composition.min_symbols],
+                allowed: composition.allow,
+                forbidden: composition.forbid,
+                no_lookalikes: composition.no_lookalikes,
+                max_repeat: composition.max_repeat,
+                max_sequence: composition.max_sequence
+            };
+
+            if speak == Some(Speech::Nato) {
+                if let Some(character) = policy.character_set().into_iter().find(|character| spell_nato(&character.to_string()).is_err()) {
+                    return Err(format!("The character {character:?} can't be spelled with the NATO alphabet").into());
+                }
+            }
+
+            let wordlist = load_speech_wordlist(speak)?;
+
+            if policy.is_constrained() {
+                let sampler = match bits {
+                    Some(bits) => PasswordSampler::for_bits(&policy, bits)?,
+                    None => PasswordSampler::new(&policy, length.unwrap())?
+                };
+
+                total = count.unwrap_or(1);
+
+                check_entropy(options, "password", keyspace_bits(sampler.keyspace()), true)?;
+
+                spawn(move || create_parallel(sender, count, &source, |_, rng| speak_item(generate_constrained_password(&sampler, rng), speak, &wordlist)))
+            }
+            else {
+                let character_set = get_character_set(&policy, &mut source.setup());
+
+                if character_set.is_empty() { return Err("The policy does not allow any characters".into()); }
+
+                let length = resolve_length(length, bits, |length| uniform_bits(character_set.len(), length))?;
+
+                total = count.unwrap_or(1);
+
+                check_entropy(options, "password", uniform_bits(character_set.len(), length), true)?;
+
+                spawn(move || create_parallel(sender, count, &source, |_, rng| speak_item(generate_password(&character_set, length, rng), speak, &wordlist)))
+            }
         },
-        CreateCommands::Hex { uppercase, length } => {
+        CreateCommands::Mask { syntax, custom_charsets, mask, count } => {
+            total = count.unwrap_or(1);
+
+            let syntax = syntax.unwrap_or_else(|| MaskSyntax::detect(&mask));
+            let custom = [custom_charsets.charset1, custom_charsets.charset2, custom_charsets.charset3, custom_charsets.charset4];
+            let mask = Mask::parse(&mask, syntax, &custom)?;
+
+            info!("Keyspace: {} ({:.2} bits of entropy)", mask.keyspace(), mask.entropy());
+
+            spawn(move || create_parallel(sender, count, &source, |_, rng| generate_mask(&mask, rng)))
+        },
+        CreateCommands::Regex { pattern, count } => {
+            total = count.unwrap_or(1);
+
+            let pattern = Pattern::parse(&pattern)?;
+
+            info!("Keyspace: {} ({:.2} bits of entropy)", pattern.keyspace(), keyspace_bits(pattern.keyspace()));
+
+            spawn(move || create_parallel(sender, count, &source, |_, rng| generate_pattern(&pattern, rng)))
+        },
+        CreateCommands::Mnemonic { words, check, derive_seed, passphrase, count } => {
+            let wordlist = load_bip39_wordlist()?;
+            let words = words.parse::<usize>()?;
+            let with_seed = move |mnemonic: Vec<u8>| {
+                if !derive_seed { return mnemonic; }
+
+                let seed = hex::encode(derive_mnemonic_seed(from_utf8(&mnemonic).unwrap(), &passphrase));
+
+                [mnemonic, b"\n".to_vec(), seed.into_bytes()].concat()
+            };
+
+            if let Some(mnemonic) = check {
+                total = 1;
+
+                let mnemonic = validate_mnemonic(&wordlist, &mnemonic)?;
+
+                info!("The mnemonic is valid");
+
+                spawn(move || create_serial(sender, || with_seed(mnemonic.into_bytes())))
+            }
+            else {
+                total = count.unwrap_or(1);
+
+                check_entropy(options, "mnemonic", (words * 32 / 3) as f64, true)?;
+
+                spawn(move || create_parallel(sender, count, &source, |_, rng| with_seed(generate_mnemonic(&wordlist, words, rng))))
+            }
+        },
+        CreateCommands::Otp { command } => match command {
+            OtpCommands::Secret { length, count } => {
+                total = count.unwrap_or(1);
+
+                check_entropy(options, "secret", uniform_bits(256, length), true)?;
+
+                spawn(move || create_parallel(sender, count, &source, |_, rng| generate_otp_secret(length, rng)))
+            },
+            OtpCommands::Uri { account, issuer, secret, length, parameters } => {
+                total = 1;
+
+                let parameters = OtpParameters { algorithm: parameters.algorithm, digits: parameters.digits, period: parameters.period };
+                let secret = match secret {
+                    Some(secret) => {
+                        decode_otp_secret(&secret)?;
+
+                        normalize_otp_secret(&secret)
+                    },
+                    None => {
+                        check_entropy(options, "secret", uniform_bits(256, length), true)?;
+
+                        String::from_utf8(generate_otp_secret(length, &mut source.stream(0)))?
+                    }
+                };
+
+                spawn(move || create_serial(sender, || create_otp_uri(&secret, &account, issuer.as_deref(), &parameters).into_bytes()))
+            },
+            OtpCommands::Code { counter, time, parameters, secret } => {
+                total = 1;
+
+                let parameters = OtpParameters { algorithm: parameters.algorithm, digits: parameters.digits, period: parameters.period };
+                let secret = decode_otp_secret(&secret)?;
+                let code = match counter {
+                    Some(counter) => compute_hotp(&secret, counter, &parameters),
+                    None => {
+                        let timestamp = time.unwrap_or_else(unix_timestamp_secs);
+
+                        if time.is_none() {
+                            info!("The code is valid for another {} seconds", parameters.period - timestamp % parameters.period);
+                        }
+
+                        compute_totp(&secret, timestamp, &parameters)
+                    }
+                };
+
+                spawn(move || create_serial(sender, || code.into_bytes()))
+            }
+        },
+        CreateCommands::SshKey { comment, output, encrypt, passphrase } => {
             total = 1;
 
-            spawn(move || create_serial(sender, || generate_hex(uppercase, length)))
+            // A generated passphrase is printed before anything else, since it can't be recovered from the key.
+            let (passphrase, generated) = match encrypt {
+                Some(length) => {
+                    let wordlist = load_default_wordlist(&mut source.setup())?;
+
+                    check_entropy(options, "passphrase", uniform_bits(wordlist.len(), length), true)?;
+
+                    (Some(String::from_utf8(generate_passphrase(&wordlist, " ", length, &mut source.stream(1)))?), true)
+                },
+                None => (passphrase, false)
+            };
+
+            let keypair = generate_ssh_key(&comment, passphrase.as_deref(), &mut source.stream(0))?;
+            let mut lines = Vec::new();
+
+            info!("{}", keypair.fingerprint);
+
+            if generated { lines.push(passphrase.unwrap_or_default()); }
+
+            match output {
+                Some(path) => {
+                    let mut public_path = path.clone().into_os_string();
+
+                    public_path.push(".pub");
+
+                    write_private_file(&path, keypair.private.as_bytes())?;
+                    File::create(public_path)?.write_all(format!("{}\n", keypair.public).as_bytes())?;
+                },
+                None => {
+                    lines.push(keypair.private.trim_end().to_string());
+                    lines.push(keypair.public);
+                }
+            }
+
+            spawn(move || create_serial(sender, || lines.join("\n").into_bytes()))
         },
-        CreateCommands::Base64 { url_safe, length } => {
+        CreateCommands::Cert { key, name, subject_alt_names, ca, days, issuer_cert, issuer_key, output } => {
             total = 1;
 
-            spawn(move || create_serial(sender, || generate_base64(url_safe, length)))
+            let request = CertificateRequest { key, name, subject_alt_names, ca, not_before: unix_timestamp_secs(), days };
+            let issuer = match (issuer_cert, issuer_key) {
+                (Some(certificate), Some(private_key)) => Some((String::from_utf8(read_in(Some(certificate))?)?, String::from_utf8(read_in(Some(private_key))?)?)),
+                _ => None
+            };
+            let issuer = issuer.as_ref().map(|(certificate, private_key)| CertificateIssuer { certificate, private_key });
+            let bundle = generate_certificate(&request, issuer.as_ref(), &mut source.stream(0))?;
+            let mut lines = Vec::new();
+
+            match output {
+                Some(path) => {
+                    let mut certificate_path = path.clone().into_os_string();
+                    let mut key_path = path.into_os_string();
+
+                    certificate_path.push(".pem");
+                    key_path.push(".key");
+
+                    write_private_file(Path::new(&key_path), bundle.private_key.as_bytes())?;
+                    File::create(certificate_path)?.write_all(bundle.certificate.as_bytes())?;
+                },
+                None => {
+                    lines.push(bundle.certificate.trim_end().to_string());
+                    lines.push(bundle.private_key.trim_end().to_string());
+                }
+            }
+
+            spawn(move || create_serial(sender, || lines.join("\n").into_bytes()))
         },
-        CreateCommands::Password { numbers, symbols, length, count } => {
+        CreateCommands::Wireguard { preshared, count } => {
             total = count.unwrap_or(1);
 
-            let character_set = get_character_set(numbers, symbols);
+            // Clamping fixes five of the 256 bits of an X25519 private key.
+            check_entropy(options, "private key", 251.0, true)?;
 
-            spawn(move || create_parallel(sender, count, || generate_password(&character_set, length)))
+            spawn(move || create_parallel(sender, count, &source, |_, rng| generate_wireguard_keys(preshared, rng)))
         },
-        CreateCommands::Passphrase { path, delimiter, separator, length, count } => {
+        CreateCommands::Hashcash { bits, resource, count } => {
             total = count.unwrap_or(1);
 
-            let mut rng = thread_rng();
-            let wordlist = get_wordlist(path, Some(&delimiter), &mut rng)?;
+            if resource.contains(':') { return Err("The resource of a stamp can't contain a colon".into()); }
+            if bits > 32 { warn!("A stamp with {bits} bits takes about 2^{bits} hashes to mint"); }
+
+            let timestamp = unix_timestamp_secs();
+
+            spawn(move || create_parallel(sender, count, &source, |_, rng| mint_hashcash(&resource, bits, timestamp, rng)))
+        },
+        CreateCommands::Token { prefix, alphabet, length, bits, count } => {
+            total = count.unwrap_or(1);
+
+            let alphabet = alphabet.chars().collect::<Vec<_>>();
+
+            check_token_alphabet(&alphabet)?;
+
+            let length = resolve_length(Some(length), bits, |length| uniform_bits(alphabet.len(), length))?;
+
+            check_entropy(options, "token", uniform_bits(alphabet.len(), length), true)?;
+
+            spawn(move || create_parallel(sender, count, &source, |_, rng| generate_token(&prefix, &alphabet, length, rng)))
+        },
+        CreateCommands::AgeIdentity { count } => {
+            total = count.unwrap_or(1);
+
+            check_entropy(options, "identity", 251.0, true)?;
+
+            spawn(move || create_parallel(sender, count, &source, |_, rng| generate_age_identity(rng)))
+        },
+        CreateCommands::Passphrase { path, wordlist, delimiter, separator, transforms, physical, bits, bits_count, length, count } => {
+            let count = count.or(bits_count);
+
+            let style = PassphraseStyle {
+                capitalization: transforms.capitalization,
+                digit: transforms.digit,
+                symbol: transforms.symbol,
+                separators: transforms.separators.chars().collect(),
+                leet: transforms.leet,
+                unique: transforms.unique
+            };
+
+            let input = match (physical.dice, physical.coins, physical.cards) {
+                (Some(rolls), _, _) => Some(PhysicalInput::dice(&rolls)?),
+                (_, Some(flips), _) => Some(PhysicalInput::coins(&flips)?),
+                (_, _, Some(deck)) => Some(PhysicalInput::cards(&deck)?),
+                _ => None
+            };
+
+            if let Some(input) = input {
+                total = 1;
+
+                if !style.is_plain() { return Err("Passphrases chosen with physical randomness can't be transformed".into()); }
 
-            spawn(move || create_parallel(sender, count, || generate_passphrase(&wordlist, &separator, length)))
+                let wordlist = load_builtin_wordlist(wordlist.as_deref().unwrap_or(DEFAULT_WORDLIST))?;
+                let length = resolve_length(length, bits, |length| uniform_bits(wordlist.len(), length))?;
+                let required = uniform_bits(wordlist.len(), length);
+
+                check_entropy(options, "passphrase", required, true)?;
+
+                info!("The input provides {:.2} bits of entropy, and the passphrase requires {required:.2}", input.entropy());
+
+                let passphrase = input.select(length, wordlist.len())?
+                    .into_iter()
+                    .map(|index| {
+                        match dice_index(index, wordlist.len()) {
+                            Some(dice) => info!("{dice} {}", wordlist[index]),
+                            None => info!("{} {}", index + 1, wordlist[index])
+                        }
+
+                        wordlist[index].as_str()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(&separator);
+
+                spawn(move || create_serial(sender, || passphrase.into_bytes()))
+            }
+            else {
+                let wordlist = get_wordlist(path, wordlist.as_deref(), Some(&delimiter), &mut source.setup())?;
+                let length = resolve_length(length, bits, |length| passphrase_entropy(&wordlist, length, &style))?;
+
+                total = count.unwrap_or(1);
+
+                if style.unique && length > wordlist.len() {
+                    return Err(format!("The wordlist only contains {} words, so a passphrase of {length} unique words is impossible", wordlist.len()).into());
+                }
+
+                check_entropy(options, "passphrase", passphrase_entropy(&wordlist, length, &style), true)?;
+
+                spawn(move || create_parallel(sender, count, &source, |_, rng| generate_styled_passphrase(&wordlist, &separator, length, &style, rng)))
+            }
         },
         CreateCommands::Username { capitalize, command } => match command {
-            UsernameCommands::Simple { length, count } => {
+            UsernameCommands::Simple { bits, bits_count, length, count } => {
+                let count = count.or(bits_count);
+                let length = resolve_length(length, bits, simple_username_entropy)?;
+
                 total = count.unwrap_or(1);
 
-                spawn(move || create_parallel(sender, count, || generate_simple_username(capitalize, length)))
+                check_entropy(options, "username", simple_username_entropy(length), false)?;
+
+                spawn(move || create_parallel(sender, count, &source, |_, rng| generate_simple_username(capitalize, length, rng)))
             },
-            UsernameCommands::Complex { length, count } => {
+            UsernameCommands::Complex { bits, bits_count, length, count } => {
+                let count = count.or(bits_count);
+                let length = resolve_length(length, bits, complex_username_entropy)?;
+
                 total = count.unwrap_or(1);
 
-                spawn(move || create_parallel(sender, count, || generate_complex_username(capitalize, length)))
+                check_entropy(options, "username", complex_username_entropy(length), false)?;
+
+                spawn(move || create_parallel(sender, count, &source, |_, rng| generate_complex_username(capitalize, length, rng)))
             }
         },
-        CreateCommands::Digits { length, count } => {
+        CreateCommands::Digits { bits, bits_count, length, count } => {
+            let count = count.or(bits_count);
+            let length = resolve_length(length, bits, |length| uniform_bits(10, length))?;
+
             total = count.unwrap_or(1);
 
-            spawn(move || create_parallel(sender, count, || generate_digits(length)))
+            check_entropy(options, "sequence of digits", uniform_bits(10, length), false)?;
+
+            spawn(move || create_parallel(sender, count, &source, |_, rng| generate_digits(length, rng)))
         },
         CreateCommands::Number { minimum, maximum, count } => {
             total = count.unwrap_or(1);
 
-            spawn(move || create_parallel(sender, count, || generate_number(minimum, maximum)))
+            spawn(move || create_parallel(sender, count, &source, |_, rng| generate_number(minimum, maximum, rng)))
+        },
+        CreateCommands::Prime { bits, safe, residue, modulus, dh, format, count } => {
+            total = count.unwrap_or(1);
+
+            let prime_options = match dh {
+                true => PrimeOptions::diffie_hellman(bits)?,
+                false => PrimeOptions::new(bits, safe, residue.zip(modulus))?
+            };
+
+            if format == PrimeFormat::Pem && !dh { return Err("PEM output is only available for Diffie-Hellman parameters".into()); }
+
+            // Primes are often public, such as Diffie-Hellman parameters, so their entropy is only reported.
+            check_entropy(options, "prime", prime_options.entropy(), false)?;
+
+            // The search only fails when no prime satisfies the options, so the first prime is found here to report that.
+            let first = generate_prime(&prime_options, &mut source.stream(0))?;
+
+            spawn(move || create_parallel(sender, count, &source, |index, rng| {
+                let prime = match index {
+                    0 => first.clone(),
+                    _ => generate_prime(&prime_options, rng).unwrap()
+                };
+
+                if dh { encode_dh_parameters(&prime, DH_GENERATOR, format) }
+                else { encode_prime(&prime, format) }
+            }))
         },
         CreateCommands::Markov {
             capitalize,
@@ -140,6 +634,7 @@ pub fn handle_create_command(command: CreateCommands) -> UnitResult<'static> {
             length_range,
             model_parameters,
             cache_control,
+            bits,
             count
         } => {
             total = count.unwrap_or(1);
@@ -149,12 +644,33 @@ pub fn handle_create_command(command: CreateCommands) -> UnitResult<'static> {
             let cache_control = (cache_control.no_cache, cache_control.rebuild_cache);
             let generator = get_generator(path, model_parameters, cache_control)?;
 
-            spawn(move || create_parallel(sender, count, || generate_markov(
+            // Markov words are not uniformly distributed, so their entropy can only be estimated by sampling the model.
+            let (minimum, maximum, estimate) = match bits {
+                Some(bits) => {
+                    let (minimum, maximum, estimate) = markov_range_for_bits(&generator, bits, minimum, maximum, &mut source.setup())?;
+
+                    info!("Generating words of at least {minimum} and fewer than {maximum} letters");
+
+                    (minimum, maximum, Some(estimate))
+                },
+                None => (minimum, maximum, None)
+            };
+
+            if options.report {
+                let estimate = match estimate {
+                    Some(estimate) => estimate,
+                    None => estimate_markov_entropy(&generator, minimum, maximum, &mut source.setup())?
+                };
+
+                info!("Entropy: about {:.2} bits per word (estimated)", estimate.entropy);
+            }
+
+            spawn(move || create_parallel(sender, count, &source, |_, rng| generate_markov(
                 &generator,
                 capitalize,
                 minimum,
                 maximum,
-                &mut thread_rng()
+                rng
             )))
         }
     };
@@ -166,11 +682,334 @@ pub fn handle_create_command(command: CreateCommands) -> UnitResult<'static> {
     Ok(())
 }
 
-pub fn handle_analyze_command(input: Option<PathBuf>) -> UnitResult<'static> {
+pub fn handle_list_command(command: ListCommands) -> UnitResult<'static> {
+    match command {
+        ListCommands::Wordlists => println!("{}", describe_wordlists())
+    };
+
+    Ok(())
+}
+
+pub fn handle_hash_command(algorithm: HashAlgorithm, generate: Option<usize>, verify: Option<String>, costs: HashCosts, seed: Option<u64>) -> UnitResult<'static> {
+    let source = RandomSource::new(seed);
+    let password = match generate {
+        Some(length) => {
+            let character_set = get_character_set(&PasswordPolicy::default(), &mut source.setup());
+
+            generate_password(&character_set, length, &mut source.stream(0))
+        },
+        None => {
+            let mut buffer = read_in(None)?;
+
+            // Passwords piped from another command usually end with a line feed which isn't part of the password.
+            if buffer.ends_with(b"\n") { buffer.pop(); }
+            if buffer.ends_with(b"\r") { buffer.pop(); }
+
+            buffer
+        }
+    };
+
+    if let Some(hash) = verify {
+        if !verify_password(&password, &hash)? { return Err("The password does not match the hash".into()); }
+
+        info!("The password matches the hash");
+
+        return Ok(());
+    }
+
+    if algorithm == HashAlgorithm::Bcrypt && password.len() > 72 {
+        warn!("bcrypt only uses the first 72 bytes of a password");
+    }
+
+    let parameters = HashParameters {
+        memory: costs.memory,
+        time: costs.time,
+        parallelism: costs.parallelism,
+        log_n: costs.log_n,
+        block_size: costs.block_size,
+        iterations: costs.iterations,
+        cost: costs.cost
+    };
+    // The salt is never seeded, since a salt shared by every hash made with the same seed is no salt at all.
+    let hash = hash_password(&password, algorithm, &parameters, &mut OsRng)?;
+
+    if generate.is_some() { println!("{}", from_utf8(&password)?); }
+
+    println!("{hash}");
+
+    Ok(())
+}
+
+pub fn handle_crypt_command(encrypt: bool, keys: KeySources, output: Option<PathBuf>, input: Option<PathBuf>) -> UnitResult<'static> {
+    let source = match (keys.key_file, keys.passphrase, keys.passphrase_file) {
+        (Some(path), _, _) => KeySource::Key(parse_key(&read_in(Some(path))?)?),
+        (_, Some(passphrase), _) => KeySource::Passphrase(passphrase.into_bytes()),
+        (_, _, Some(path)) => {
+            let mut passphrase = read_in(Some(path))?;
+
+            if passphrase.ends_with(b"\n") { passphrase.pop(); }
+            if passphrase.ends_with(b"\r") { passphrase.pop(); }
+
+            KeySource::Passphrase(passphrase)
+        },
+        _ => return Err("A key file or a passphrase is required".into())
+    };
+
+    let mut reader: Box<dyn Read> = match input {
+        Some(path) => Box::new(File::open(path)?),
+        None => Box::new(stdin().lock())
+    };
+    let mut crypt = |writer: &mut dyn Write| -> Result<u64, BoxedError<'static>> {
+        let mut writer = BufWriter::new(writer);
+
+        // The nonces and salt are never seeded, since reusing them with the same key would reuse the keystream.
+        if encrypt { encrypt_stream(&mut reader, &mut writer, &source, &mut OsRng) }
+        else { decrypt_stream(&mut reader, &mut writer, &source) }
+    };
+
+    match output {
+        Some(path) => {
+            // The output is only moved into place once every chunk has been processed, so a failure leaves nothing behind.
+            let mut partial = path.clone().into_os_string();
+
+            partial.push(".partial");
+
+            let mut file = create_private_file(Path::new(&partial))?;
+
+            match crypt(&mut file) {
+                Ok(_) => rename(&partial, path)?,
+                Err(error) => {
+                    remove_file(&partial)?;
+
+                    return Err(error);
+                }
+            }
+        },
+        None => { crypt(&mut stdout().lock())?; }
+    };
+
+    Ok(())
+}
+
+pub fn handle_split_command(threshold: usize, count: usize, encoding: ShareEncoding, input: Option<PathBuf>) -> UnitResult<'static> {
+    let secret = read_in(input)?;
+    let wordlist = load_canonical_wordlist()?;
+    // The coefficients are never seeded, since secrets split with the same coefficients leak through their shares.
+    let shares = split_secret(&secret, threshold, count, &mut OsRng)?;
+
+    info!("Any {threshold} of these {count} shares will recover the secret");
+
+    for share in shares {
+        println!("{}", encode_share(&share, encoding, &wordlist));
+    }
+
+    Ok(())
+}
+
+pub fn handle_combine_command(encoding: ShareEncoding, shares: Vec<String>) -> UnitResult<'static> {
+    let wordlist = load_canonical_wordlist()?;
+    let shares = match shares.is_empty() {
+        true => String::from_utf8(read_in(None)?)?.lines().filter(|line| !line.trim().is_empty()).map(str::to_string).collect(),
+        false => shares
+    };
+    let shares = shares.iter()
+        .enumerate()
+        .map(|(position, share)| decode_share(share, encoding, &wordlist).map_err(|error| format!("Share {} could not be decoded: {error}", position + 1).into()))
+        .collect::<Result<Vec<_>, BoxedError>>()?;
+    let secret = combine_shares(&shares)?;
+    let mut stdout = stdout();
+
+    stdout.write_all(&secret)?;
+    stdout.flush()?;
+
+    Ok(())
+}
+
+pub fn handle_mac_command(algorithm: MacAlgorithm, key_file: PathBuf, verify: Option<String>, input: Option<PathBuf>) -> UnitResult<'static> {
+    let key = read_in(Some(key_file))?;
+    let mut reader: Box<dyn Read> = match input {
+        Some(path) => Box::new(File::open(path)?),
+        None => Box::new(stdin().lock())
+    };
+
+    if let Some(tag) = verify {
+        let tag = hex::decode(tag.trim()).map_err(|_| "The tag must be encoded as hexadecimal")?;
+
+        if !verify_mac(&key, &mut reader, algorithm, &tag)? { return Err("The tag does not match the data".into()); }
+
+        info!("The tag matches the data");
+
+        return Ok(());
+    }
+
+    println!("{}", hex::encode(compute_mac(&key, &mut reader, algorithm)?));
+
+    Ok(())
+}
+
+fn read_salt(salt: SaltOptions) -> Result<Vec<u8>, BoxedError<'static>> {
+    match (salt.salt, salt.salt_hex) {
+        (Some(salt), _) => Ok(salt.into_bytes()),
+        (_, Some(salt)) => Ok(hex::decode(salt.trim()).map_err(|_| "The salt must be encoded as hexadecimal")?),
+        _ => Ok(Vec::new())
+    }
+}
+
+pub fn handle_derive_command(command: DeriveCommands) -> UnitResult<'static> {
+    match command {
+        DeriveCommands::Hkdf { algorithm, salt, info, length, extract_only, prk, input } => {
+            let secret = read_in(input)?;
+            let salt = read_salt(salt)?;
+
+            if extract_only {
+                println!("{}", hex::encode(hkdf_extract(&salt, &secret, algorithm)));
+
+                return Ok(());
+            }
+
+            let key = match prk {
+                true => hex::decode(String::from_utf8_lossy(&secret).trim()).map_err(|_| "The pseudorandom key must be encoded as hexadecimal")?,
+                false => hkdf_extract(&salt, &secret, algorithm)
+            };
+            let labels = match info.is_empty() {
+                true => vec![String::new()],
+                false => info
+            };
+
+            if !prk && salt.is_empty() { warn!("No salt was given, so HKDF will use a salt of zeros"); }
+
+            for label in labels {
+                println!("{}", hex::encode(hkdf_expand(&key, label.as_bytes(), length, algorithm)?));
+            }
+        },
+        DeriveCommands::Pbkdf2 { algorithm, salt, iterations, length, input } => {
+            let mut password = read_in(input)?;
+            let salt = read_salt(salt)?;
+
+            // Passwords piped from another command usually end with a line feed which isn't part of the password.
+            if password.ends_with(b"\n") { password.pop(); }
+            if password.ends_with(b"\r") { password.pop(); }
+
+            if salt.is_empty() { warn!("No salt was given, so the same password will always derive the same key"); }
+
+            println!("{}", hex::encode(pbkdf2(&password, &salt, iterations, length, algorithm)?));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn handle_encode_command(encoding: Option<Encoding>, settings: EncodingSettings, literal: LiteralArgs, from: Option<Encoding>, lenient: bool, input: Option<PathBuf>) -> UnitResult<'static> {
+    let options = settings.into();
+    let mut reader: Box<dyn Read> = match input {
+        Some(path) => Box::new(File::open(path)?),
+        None => Box::new(stdin().lock())
+    };
+    let mut writer = BufWriter::new(stdout().lock());
+
+    if let Some(encoding) = encoding { check_encoding_options(encoding, &options)?; }
+
+    match (encoding, literal.language, from) {
+        (Some(encoding), _, None) => { encode_stream(&mut reader, &mut writer, encoding, &options)?; },
+        (encoding, language, _) => {
+            let mut bytes = Vec::new();
+
+            reader.read_to_end(&mut bytes)?;
+
+            if let Some(from) = from {
+                bytes = decode_text(&bytes, from, &EncodingOptions::default(), lenient)?;
+            }
+
+            match (encoding, language) {
+                (Some(encoding), _) => writer.write_all(encode_bytes(&bytes, encoding, &options)?.as_bytes())?,
+                (None, Some(language)) => writer.write_all(format_literal(&bytes, language, &(&literal).into())?.as_bytes())?,
+                _ => return Err("An encoding or a language is required".into())
+            }
+        }
+    }
+
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+pub fn handle_decode_command(encoding: Option<Encoding>, speak: Option<Speech>, lenient: bool, hrp: Option<String>, label: Option<String>, input: Option<PathBuf>) -> UnitResult<'static> {
+    let mut reader: Box<dyn Read> = match input {
+        Some(path) => Box::new(File::open(path)?),
+        None => Box::new(stdin().lock())
+    };
+    let mut writer = BufWriter::new(stdout().lock());
+
+    match (speak, encoding) {
+        (Some(speech), _) => {
+            let mut text = String::new();
+
+            reader.read_to_string(&mut text)?;
+            writer.write_all(&read_speech(&text, speech, &load_speech_wordlist(speak)?)?)?;
+        },
+        (None, encoding) => {
+            let encoding = encoding.ok_or("An encoding or a language is required")?;
+
+            decode_stream(&mut reader, &mut writer, encoding, &EncodingOptions { uppercase: false, hrp, label }, lenient)?;
+        }
+    }
+
+    writer.flush()?;
+
+    Ok(())
+}
+
+pub fn handle_verify_command(command: VerifyCommands) -> UnitResult<'static> {
+    match command {
+        VerifyCommands::Hashcash { bits, resource, max_age, stamp } => {
+            let requirements = HashcashRequirements { bits, resource: resource.as_deref(), now: unix_timestamp_secs(), max_age };
+
+            verify_hashcash(&stamp, &requirements)?;
+
+            info!("The stamp is valid");
+        },
+        VerifyCommands::Token { prefix, alphabet, length, token } => {
+            let alphabet = alphabet.chars().collect::<Vec<_>>();
+
+            check_token_alphabet(&alphabet)?;
+            verify_token(&token, prefix.as_deref(), &alphabet, length)?;
+
+            info!("The token is valid");
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * Write each digest of a report as words to be read aloud, one digest to a line.
+ */
+fn speak_digests<'a>(report: &Report, speech: Speech, wordlist: &[String]) -> Result<String, BoxedError<'a>> {
+    let digests = [("MD5", &report.md5), ("SHA1", &report.sha1), ("SHA2-256", &report.sha256), ("SHA2-512", &report.sha512)];
+    let lines = digests.into_iter()
+        .map(|(name, digest)| Ok(format!("{name}: {}", speak_bytes(&hex::decode(digest)?, speech, wordlist)?)))
+        .collect::<Result<Vec<_>, BoxedError<'a>>>()?;
+
+    Ok(lines.join("\n"))
+}
+
+pub fn handle_analyze_command(pem: bool, speak: Option<Speech>, input: Option<PathBuf>) -> UnitResult<'static> {
     let buffer = read_in(input)?;
-    let report = analyze(buffer);
+    let wordlist = load_speech_wordlist(speak)?;
+    let reports = match pem {
+        true => analyze_pem(&buffer)?,
+        false => vec![(String::new(), analyze(buffer))]
+    };
 
-    println!("{report}");
+    for (tag, report) in reports {
+        match pem {
+            true => println!("{tag}\n{report}"),
+            false => println!("{report}")
+        }
+
+        if let Some(speech) = speak { println!("{}", speak_digests(&report, speech, &wordlist)?); }
+    }
 
     Ok(())
 }
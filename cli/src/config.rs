@@ -1,7 +1,8 @@
 use std::path::PathBuf;
 
 use clap::{Parser, Subcommand, Args, ArgAction};
-use lib::TimestampFormats;
+use num_bigint::BigUint;
+use lib::{MAX_HASHCASH_BITS, BASE62_ALPHABET, DEFAULT_TOKEN_LENGTH, Capitalization, CertificateKey, Encoding, EncodingOptions, Language, LiteralOptions, HashAlgorithm, MacAlgorithm, MaskSyntax, OtpAlgorithm, PrimeFormat, ShareEncoding, Speech, TimestampFormats};
 use log::LevelFilter;
 
 type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
@@ -14,7 +15,10 @@ pub struct Arguments {
     pub command: Commands,
 
     #[command(flatten)]
-    pub verbosity: Verbosity
+    pub verbosity: Verbosity,
+
+    #[arg(long = "seed", help = "Seed the random number generator to produce reproducible output", global = true)]
+    pub seed: Option<u64>
 }
 
 #[derive(Args)]
@@ -34,12 +38,148 @@ pub struct Verbosity {
 pub enum Commands {
     /// Create a parcel of data of the specified type
     Create {
+        #[command(flatten)]
+        entropy: EntropyOptions,
+
         /// The sub-command to execute
         #[command(subcommand)]
-        command: CreateCommands
+        command: Box<CreateCommands>
+    },
+    /// List the built-in resources of the specified type
+    List {
+        #[command(subcommand)]
+        command: ListCommands
+    },
+    /// Hash a password read from STDIN for storage, or verify it against an existing hash
+    Hash {
+        #[arg(value_enum, short = 'a', long = "algorithm", help = "The password hashing scheme to use", default_value = "argon2id")]
+        algorithm: HashAlgorithm,
+
+        #[arg(short = 'g', long = "generate", help = "Generate a password of this many characters instead of reading one, and print it before its hash", num_args = 0..=1, default_missing_value = "20", conflicts_with = "verify")]
+        generate: Option<usize>,
+
+        #[arg(long = "verify", help = "Check the password against this hash instead of hashing it")]
+        verify: Option<String>,
+
+        #[command(flatten)]
+        costs: HashCosts
+    },
+    /// Encrypt a file or STDIN with XChaCha20-Poly1305, using a key file or a passphrase
+    Encrypt {
+        #[command(flatten)]
+        keys: KeySources,
+
+        #[arg(short = 'o', long = "output", help = "A path on a filesystem where data should be written, or leave empty to write to STDOUT")]
+        output: Option<PathBuf>,
+
+        /// A path to a file on a filesystem, or leave empty to read from STDIN
+        input: Option<PathBuf>
+    },
+    /// Decrypt a file or STDIN which was encrypted by the encrypt command
+    Decrypt {
+        #[command(flatten)]
+        keys: KeySources,
+
+        #[arg(short = 'o', long = "output", help = "A path on a filesystem where data should be written, or leave empty to write to STDOUT")]
+        output: Option<PathBuf>,
+
+        /// A path to a file on a filesystem, or leave empty to read from STDIN
+        input: Option<PathBuf>
+    },
+    /// Split a secret read from a file or STDIN into shares, any threshold of which can recover it
+    Split {
+        #[arg(short = 't', long = "threshold", help = "The number of shares needed to recover the secret")]
+        threshold: usize,
+
+        #[arg(short = 'n', long = "shares", help = "The number of shares to create")]
+        shares: usize,
+
+        #[arg(value_enum, short = 'e', long = "encoding", help = "How to write each share", default_value = "hex")]
+        encoding: ShareEncoding,
+
+        /// A path to a file on a filesystem, or leave empty to read from STDIN
+        input: Option<PathBuf>
+    },
+    /// Recover a secret from shares created by the split command
+    Combine {
+        #[arg(value_enum, short = 'e', long = "encoding", help = "How each share is written", default_value = "hex")]
+        encoding: ShareEncoding,
+
+        /// The shares, or leave empty to read one share per line from STDIN
+        shares: Vec<String>
+    },
+    /// Compute the HMAC of a file or STDIN with a key file, or verify it against an existing tag
+    Mac {
+        #[arg(value_enum, short = 'a', long = "algorithm", help = "The hash function used by HMAC", default_value = "sha256")]
+        algorithm: MacAlgorithm,
+
+        #[arg(short = 'k', long = "key-file", help = "A file whose contents are used as the key, byte for byte")]
+        key_file: PathBuf,
+
+        #[arg(long = "verify", help = "Check the data against this hexadecimal tag instead of printing its tag")]
+        verify: Option<String>,
+
+        /// A path to a file on a filesystem, or leave empty to read from STDIN
+        input: Option<PathBuf>
+    },
+    /// Derive keys from a secret read from a file or STDIN
+    Derive {
+        #[command(subcommand)]
+        command: DeriveCommands
+    },
+    /// Encode a file or STDIN as text or as an array literal, or transcode it from another encoding
+    Encode {
+        #[arg(value_enum, short = 'e', long = "encoding", help = "The encoding to write", required_unless_present = "language", conflicts_with = "language")]
+        encoding: Option<Encoding>,
+
+        #[command(flatten)]
+        settings: EncodingSettings,
+
+        #[command(flatten)]
+        literal: LiteralArgs,
+
+        #[arg(value_enum, short = 'f', long = "from", help = "Decode the input from this encoding first, instead of reading it raw")]
+        from: Option<Encoding>,
+
+        #[arg(short = 'l', long = "lenient", help = "Ignore whitespace, missing padding and other non-canonical input when decoding", requires = "from")]
+        lenient: bool,
+
+        /// A path to a file on a filesystem, or leave empty to read from STDIN
+        input: Option<PathBuf>
+    },
+    /// Decode text read from a file or STDIN, and write the raw bytes
+    Decode {
+        #[arg(value_enum, short = 'e', long = "encoding", help = "The encoding to read", required_unless_present = "speak")]
+        encoding: Option<Encoding>,
+
+        #[arg(value_enum, long = "speak", help = "Read words written to be read aloud, where NATO spelling is read back as the characters it spells", conflicts_with_all = ["encoding", "lenient", "hrp", "label"])]
+        speak: Option<Speech>,
+
+        #[arg(short = 'l', long = "lenient", help = "Ignore whitespace, missing padding and other non-canonical input")]
+        lenient: bool,
+
+        #[arg(long = "hrp", help = "The human-readable part Bech32 must have")]
+        hrp: Option<String>,
+
+        #[arg(long = "label", help = "The label PEM armor must have")]
+        label: Option<String>,
+
+        /// A path to a file on a filesystem, or leave empty to read from STDIN
+        input: Option<PathBuf>
+    },
+    /// Verify a stamp or token
+    Verify {
+        #[command(subcommand)]
+        command: VerifyCommands
     },
     /// Cryptographically analyze a piece of data
     Analyze {
+        #[arg(short = 'p', long = "pem", help = "Analyze the contents of each PEM block, whose digests are the fingerprints of a certificate")]
+        pem: bool,
+
+        #[arg(value_enum, long = "speak", help = "Also write each digest as words to be read aloud")]
+        speak: Option<Speech>,
+
         /// A path to a file on a filesystem, or leave empty to read from STDIN
         input: Option<PathBuf>
     },
@@ -63,26 +203,64 @@ pub enum CreateCommands {
         #[command(subcommand)]
         command: TimestampCommands
     },
+    /// Generate a unique identifier
+    Id {
+        #[command(subcommand)]
+        command: IdCommands
+    },
     /// Generate random bytes
     Bytes {
+        #[arg(value_enum, short = 'e', long = "encoding", help = "Write the bytes as text in this encoding instead of writing them raw", conflicts_with_all = ["language", "speak"])]
+        encoding: Option<Encoding>,
+
+        #[command(flatten)]
+        settings: EncodingSettings,
+
+        #[command(flatten)]
+        literal: LiteralArgs,
+
+        #[arg(value_enum, long = "speak", help = "Write the bytes as words to be read aloud", conflicts_with = "language")]
+        speak: Option<Speech>,
+
+        #[arg(long = "bits", help = "The number of bits of entropy to aim for, from which the length is worked out")]
+        bits: Option<usize>,
+
         /// The number of bytes to generate
-        length: usize
+        #[arg(required_unless_present = "bits", conflicts_with = "bits")]
+        length: Option<usize>
     },
     /// Generate random bytes and encode them as a hexadecimal string
     Hex {
         #[arg(short = 'u', long = "uppercase", help = "Print hexadecimal digits in uppercase")]
         uppercase: bool,
 
+        #[command(flatten)]
+        literal: LiteralArgs,
+
+        #[arg(value_enum, long = "speak", help = "Write the bytes as words to be read aloud", conflicts_with_all = ["language", "uppercase"])]
+        speak: Option<Speech>,
+
+        #[arg(long = "bits", help = "The number of bits of entropy to aim for, from which the length is worked out")]
+        bits: Option<usize>,
+
         /// The number of bytes to generate
-        length: usize
+        #[arg(required_unless_present = "bits", conflicts_with = "bits")]
+        length: Option<usize>
     },
     /// Generate random bytes and encode them as a Base64 string
     Base64 {
         #[arg(short = 'u', long = "url-safe", help = "Use a URL-safe alphabet")]
         url_safe: bool,
 
+        #[command(flatten)]
+        literal: LiteralArgs,
+
+        #[arg(long = "bits", help = "The number of bits of entropy to aim for, from which the length is worked out")]
+        bits: Option<usize>,
+
         /// The number of bytes to generate
-        length: usize
+        #[arg(required_unless_present = "bits", conflicts_with = "bits")]
+        length: Option<usize>
     },
     /// Generate a random password with a configurable character set
     Password {
@@ -92,10 +270,149 @@ pub enum CreateCommands {
         #[arg(short = 'S', long = "no-symbols", help = "Don't include any symbols", action = ArgAction::SetFalse)]
         symbols: bool,
 
-        /// The number of characters to generate
-        length: usize,
+        #[command(flatten)]
+        composition: Composition,
+
+        #[arg(value_enum, long = "speak", help = "Write each password as words to be read aloud")]
+        speak: Option<Speech>,
+
+        #[arg(long = "bits", help = "The number of bits of entropy to aim for, from which the length is worked out")]
+        bits: Option<usize>,
+
+        #[arg(long = "count", id = "bits_count", value_name = "COUNT", help = "How many passwords to generate when --bits is given", conflicts_with = "length")]
+        bits_count: Option<usize>,
+
+        /// The number of characters to generate, which is omitted when --bits is given
+        #[arg(required_unless_present = "bits", conflicts_with = "bits")]
+        length: Option<usize>,
 
         /// How many passwords to generate
+        #[arg(conflicts_with = "bits")]
+        count: Option<usize>
+    },
+    /// Generate a random string shaped by a mask, such as ?u?l?l?l-?d?d?d?d or Cvcvc##!
+    Mask {
+        #[arg(value_enum, short = 'y', long = "syntax", help = "The syntax of the mask, which is detected automatically if omitted")]
+        syntax: Option<MaskSyntax>,
+
+        #[command(flatten)]
+        custom_charsets: CustomCharsets,
+
+        /// The mask which describes the character set of each position
+        mask: String,
+
+        /// How many strings to generate
+        count: Option<usize>
+    },
+    /// Generate a random string matching a regular expression with bounded repetition
+    Regex {
+        /// The regular expression, such as ^[A-Z]{3}-\d{6}$
+        pattern: String,
+
+        /// How many strings to generate
+        count: Option<usize>
+    },
+    /// Generate a BIP39 mnemonic phrase, or validate an existing one
+    Mnemonic {
+        #[arg(short = 'w', long = "words", help = "The number of words in the mnemonic", default_value = "12", value_parser = ["12", "15", "18", "21", "24"])]
+        words: String,
+
+        #[arg(short = 'c', long = "check", help = "Validate an existing mnemonic instead of generating new ones", conflicts_with = "count")]
+        check: Option<String>,
+
+        #[arg(short = 'S', long = "derive-seed", help = "Print the seed derived from each mnemonic as a hexadecimal string")]
+        derive_seed: bool,
+
+        #[arg(short = 'P', long = "passphrase", help = "An optional passphrase used when deriving the seed", default_value = "", requires = "derive_seed")]
+        passphrase: String,
+
+        /// How many mnemonics to generate
+        count: Option<usize>
+    },
+    /// Provision and compute TOTP and HOTP one-time passwords
+    Otp {
+        #[command(subcommand)]
+        command: OtpCommands
+    },
+    /// Generate an Ed25519 keypair in the OpenSSH format
+    SshKey {
+        #[arg(short = 'C', long = "comment", help = "The comment stored with the key, usually user@host", default_value = "")]
+        comment: String,
+
+        #[arg(short = 'o', long = "output", help = "Write the private key to this path and the public key to the same path with .pub appended, instead of STDOUT")]
+        output: Option<PathBuf>,
+
+        #[arg(short = 'e', long = "encrypt", help = "Encrypt the private key with a generated passphrase of this many words, which is printed first", num_args = 0..=1, default_missing_value = "6")]
+        encrypt: Option<usize>,
+
+        #[arg(short = 'P', long = "passphrase", help = "Encrypt the private key with this passphrase", conflicts_with = "encrypt")]
+        passphrase: Option<String>
+    },
+    /// Generate a key and an X.509 certificate for it, which is self-signed unless an issuer is given
+    Cert {
+        #[arg(value_enum, short = 'k', long = "key", help = "The type of key to generate", default_value = "p256")]
+        key: CertificateKey,
+
+        #[arg(short = 'n', long = "name", help = "The common name of the subject", default_value = "localhost")]
+        name: String,
+
+        #[arg(short = 's', long = "san", help = "A DNS name or IP address the certificate is valid for, which may be repeated")]
+        subject_alt_names: Vec<String>,
+
+        #[arg(long = "ca", help = "Create a certificate authority which can issue other certificates")]
+        ca: bool,
+
+        #[arg(long = "days", help = "The number of days for which the certificate is valid", default_value = "365")]
+        days: u32,
+
+        #[arg(long = "issuer-cert", help = "The PEM certificate of the authority which signs the certificate", requires = "issuer_key")]
+        issuer_cert: Option<PathBuf>,
+
+        #[arg(long = "issuer-key", help = "The PEM private key of the authority which signs the certificate", requires = "issuer_cert")]
+        issuer_key: Option<PathBuf>,
+
+        #[arg(short = 'o', long = "output", help = "Write the certificate to this path with .pem appended and the private key with .key appended, instead of STDOUT")]
+        output: Option<PathBuf>
+    },
+    /// Generate a WireGuard keypair, and optionally a preshared key
+    Wireguard {
+        #[arg(short = 'p', long = "preshared", help = "Also generate a preshared key")]
+        preshared: bool,
+
+        /// How many keypairs to generate
+        count: Option<usize>
+    },
+    /// Mint a Hashcash proof-of-work stamp for a resource
+    Hashcash {
+        #[arg(short = 'b', long = "bits", help = "The number of leading zero bits, each of which doubles the work", default_value = "20", value_parser = clap::value_parser!(u32).range(1..=MAX_HASHCASH_BITS as i64))]
+        bits: u32,
+
+        #[arg(short = 'r', long = "resource", help = "The resource the stamp is for, such as an email address or the name of a form")]
+        resource: String,
+
+        /// How many stamps to mint
+        count: Option<usize>
+    },
+    /// Generate a prefixed API token with a random body and a CRC32 checksum, like those of GitHub
+    Token {
+        #[arg(short = 'p', long = "prefix", help = "The text the token begins with, such as acme_live_, so secret scanners can recognise it", default_value = "")]
+        prefix: String,
+
+        #[arg(short = 'a', long = "alphabet", help = "The characters of the body and checksum", default_value = BASE62_ALPHABET)]
+        alphabet: String,
+
+        #[arg(short = 'l', long = "length", help = "The number of random characters in the body", default_value_t = DEFAULT_TOKEN_LENGTH, conflicts_with = "bits")]
+        length: usize,
+
+        #[arg(long = "bits", help = "The number of bits of entropy to aim for, from which the length is worked out")]
+        bits: Option<usize>,
+
+        /// How many tokens to generate
+        count: Option<usize>
+    },
+    /// Generate an age identity and its recipient
+    AgeIdentity {
+        /// How many identities to generate
         count: Option<usize>
     },
     /// Generate a passphrase composed of words chosen at random from a wordlist
@@ -103,16 +420,33 @@ pub enum CreateCommands {
         #[arg(short = 'p', long = "path", help = "The wordlist file to read into memory")]
         path: Option<PathBuf>,
 
+        #[arg(short = 'w', long = "wordlist", help = "The name of a built-in wordlist to use, as shown by list wordlists", conflicts_with = "path")]
+        wordlist: Option<String>,
+
         #[arg(short = 'D', long = "delimiter", help = "The string used to separate words from each other in the wordlist", default_value = "\n")]
         delimiter: String,
 
         #[arg(short = 's', long = "separator", help = "A string used to separate words in the passphrase", default_value = " ")]
         separator: String,
 
-        /// The number of words to generate
-        length: usize,
+        #[command(flatten)]
+        transforms: PassphraseTransforms,
+
+        #[command(flatten)]
+        physical: PhysicalRandomness,
+
+        #[arg(long = "bits", help = "The number of bits of entropy to aim for, from which the length is worked out")]
+        bits: Option<usize>,
+
+        #[arg(long = "count", id = "bits_count", value_name = "COUNT", help = "How many passphrases to generate when --bits is given", conflicts_with = "length")]
+        bits_count: Option<usize>,
+
+        /// The number of words to generate, which is omitted when --bits is given
+        #[arg(required_unless_present = "bits", conflicts_with = "bits")]
+        length: Option<usize>,
 
         /// How many passphrases to generate
+        #[arg(conflicts_with = "bits")]
         count: Option<usize>
     },
     /// Generate a random pronounceable username
@@ -125,10 +459,18 @@ pub enum CreateCommands {
     },
     /// Generate a random sequence of digits
     Digits {
-        /// The number of digits to generate
-        length: usize,
+        #[arg(long = "bits", help = "The number of bits of entropy to aim for, from which the length is worked out")]
+        bits: Option<usize>,
+
+        #[arg(long = "count", id = "bits_count", value_name = "COUNT", help = "How many sequences of digits to generate when --bits is given", conflicts_with = "length")]
+        bits_count: Option<usize>,
+
+        /// The number of digits to generate, which is omitted when --bits is given
+        #[arg(required_unless_present = "bits", conflicts_with = "bits")]
+        length: Option<usize>,
 
         /// How many sequences of digits to generate
+        #[arg(conflicts_with = "bits")]
         count: Option<usize>
     },
     /// Generate a random number
@@ -142,6 +484,29 @@ pub enum CreateCommands {
         /// How many numbers to generate
         count: Option<usize>
     },
+    /// Generate a random prime, a safe prime or Diffie-Hellman parameters
+    Prime {
+        #[arg(short = 'b', long = "bits", help = "The number of bits in the prime", value_parser = clap::value_parser!(u64).range(3..=16384))]
+        bits: u64,
+
+        #[arg(short = 's', long = "safe", help = "Generate a safe prime p = 2q + 1, where q is also prime")]
+        safe: bool,
+
+        #[arg(long = "residue", help = "Generate a prime congruent to this modulo --modulus", requires = "modulus")]
+        residue: Option<BigUint>,
+
+        #[arg(long = "modulus", help = "The modulus of --residue", requires = "residue")]
+        modulus: Option<BigUint>,
+
+        #[arg(long = "dh", help = "Generate Diffie-Hellman parameters, a safe prime and the generator 2", conflicts_with_all = ["safe", "residue"])]
+        dh: bool,
+
+        #[arg(value_enum, short = 'f', long = "format", help = "How to write the prime, where pem is only for Diffie-Hellman parameters", default_value = "decimal")]
+        format: PrimeFormat,
+
+        /// How many primes to generate
+        count: Option<usize>
+    },
     /// Generate a random word using a Markov model
     Markov {
         #[arg(short = 'C', long = "no-capitalize", help = "Do not capitalize words", action = ArgAction::SetFalse)]
@@ -159,6 +524,9 @@ pub enum CreateCommands {
         #[command(flatten)]
         cache_control: CacheControl,
 
+        #[arg(long = "bits", help = "The estimated number of bits of entropy to aim for, from which the length range is worked out")]
+        bits: Option<usize>,
+
         /// How many words to generate
         count: Option<usize>
     }
@@ -168,22 +536,182 @@ pub enum CreateCommands {
 pub enum UsernameCommands {
     /// Generate a simple pronounceable username that alternates between vowels and consonants
     Simple {
-        /// The number of characters to generate
-        length: usize,
+        #[arg(long = "bits", help = "The number of bits of entropy to aim for, from which the length is worked out")]
+        bits: Option<usize>,
+
+        #[arg(long = "count", id = "bits_count", value_name = "COUNT", help = "How many simple usernames to generate when --bits is given", conflicts_with = "length")]
+        bits_count: Option<usize>,
+
+        /// The number of characters to generate, which is omitted when --bits is given
+        #[arg(required_unless_present = "bits", conflicts_with = "bits")]
+        length: Option<usize>,
 
         /// How many simple usernames to generate
+        #[arg(conflicts_with = "bits")]
         count: Option<usize>
     },
     /// Generate a complex pronounceable username that is constructed from syllables
     Complex {
-        /// The number of syllables to generate
-        length: usize,
+        #[arg(long = "bits", help = "The number of bits of entropy to aim for, from which the length is worked out")]
+        bits: Option<usize>,
+
+        #[arg(long = "count", id = "bits_count", value_name = "COUNT", help = "How many syllabic usernames to generate when --bits is given", conflicts_with = "length")]
+        bits_count: Option<usize>,
+
+        /// The number of syllables to generate, which is omitted when --bits is given
+        #[arg(required_unless_present = "bits", conflicts_with = "bits")]
+        length: Option<usize>,
 
         /// How many syllabic usernames to generate
+        #[arg(conflicts_with = "bits")]
+        count: Option<usize>
+    }
+}
+
+#[derive(Subcommand)]
+pub enum IdCommands {
+    /// Generate a random version 4 UUID
+    Uuid4 {
+        /// How many UUIDs to generate
+        count: Option<usize>
+    },
+    /// Generate a time-ordered version 7 UUID
+    Uuid7 {
+        /// How many UUIDs to generate
+        count: Option<usize>
+    },
+    /// Generate a time-ordered ULID
+    Ulid {
+        /// How many ULIDs to generate
+        count: Option<usize>
+    }
+}
+
+#[derive(Subcommand)]
+pub enum OtpCommands {
+    /// Generate a random secret encoded as Base32
+    Secret {
+        #[arg(short = 'l', long = "length", help = "The number of bytes in the secret", default_value = "20")]
+        length: usize,
+
+        /// How many secrets to generate
         count: Option<usize>
+    },
+    /// Create an otpauth:// URI for provisioning an authenticator, generating a secret if none is given
+    Uri {
+        #[arg(short = 'a', long = "account", help = "The name of the account, such as an email address")]
+        account: String,
+
+        #[arg(short = 'i', long = "issuer", help = "The name of the service which issued the secret")]
+        issuer: Option<String>,
+
+        #[arg(short = 's', long = "secret", help = "An existing Base32 secret to use")]
+        secret: Option<String>,
+
+        #[arg(short = 'l', long = "length", help = "The number of bytes in a generated secret", default_value = "20", conflicts_with = "secret")]
+        length: usize,
+
+        #[command(flatten)]
+        parameters: OtpOptions
+    },
+    /// Compute the current TOTP code of a Base32 secret, or an HOTP code when a counter is given
+    Code {
+        #[arg(short = 'c', long = "counter", help = "Compute the HOTP code for this counter instead of a TOTP code")]
+        counter: Option<u64>,
+
+        #[arg(short = 't', long = "time", help = "Compute the TOTP code at this many seconds since the Unix epoch instead of now", conflicts_with = "counter")]
+        time: Option<u64>,
+
+        #[command(flatten)]
+        parameters: OtpOptions,
+
+        /// The Base32 secret shared with the authenticator
+        secret: String
+    }
+}
+
+#[derive(Subcommand)]
+pub enum DeriveCommands {
+    /// Derive a key for each label from a high-entropy secret with HKDF
+    Hkdf {
+        #[arg(value_enum, short = 'a', long = "algorithm", help = "The hash function used by HKDF", default_value = "sha256")]
+        algorithm: MacAlgorithm,
+
+        #[command(flatten)]
+        salt: SaltOptions,
+
+        #[arg(short = 'i', long = "info", help = "A label which the key is bound to, such as the name of a service, which may be repeated to derive several keys")]
+        info: Vec<String>,
+
+        #[arg(short = 'l', long = "length", help = "The number of bytes in each key", default_value = "32")]
+        length: usize,
+
+        #[arg(long = "extract-only", help = "Print the pseudorandom key made by the extract step instead of expanding it", conflicts_with_all = ["info", "length", "prk"])]
+        extract_only: bool,
+
+        #[arg(long = "prk", help = "Read a hexadecimal pseudorandom key, such as one printed by --extract-only, and skip the extract step", conflicts_with_all = ["salt", "salt_hex"])]
+        prk: bool,
+
+        /// A path to a file on a filesystem, or leave empty to read from STDIN
+        input: Option<PathBuf>
+    },
+    /// Derive a key from a password with PBKDF2
+    Pbkdf2 {
+        #[arg(value_enum, short = 'a', long = "algorithm", help = "The hash function used by PBKDF2", default_value = "sha256")]
+        algorithm: MacAlgorithm,
+
+        #[command(flatten)]
+        salt: SaltOptions,
+
+        #[arg(long = "iterations", help = "The number of iterations", default_value = "600000")]
+        iterations: u32,
+
+        #[arg(short = 'l', long = "length", help = "The number of bytes in the key", default_value = "32")]
+        length: usize,
+
+        /// A path to a file on a filesystem, or leave empty to read from STDIN
+        input: Option<PathBuf>
+    }
+}
+
+#[derive(Subcommand)]
+pub enum VerifyCommands {
+    /// Check the format, bits, resource and date of a Hashcash stamp
+    Hashcash {
+        #[arg(short = 'b', long = "bits", help = "The fewest leading zero bits the stamp may claim", default_value = "20")]
+        bits: u32,
+
+        #[arg(short = 'r', long = "resource", help = "The resource the stamp must have been minted for")]
+        resource: Option<String>,
+
+        #[arg(long = "max-age", help = "The most seconds which may have passed since the stamp was minted", default_value = "172800")]
+        max_age: u64,
+
+        /// The stamp to verify
+        stamp: String
+    },
+    /// Check the prefix, alphabet, length and checksum of a token
+    Token {
+        #[arg(short = 'p', long = "prefix", help = "The text the token must begin with")]
+        prefix: Option<String>,
+
+        #[arg(short = 'a', long = "alphabet", help = "The characters of the body and checksum", default_value = BASE62_ALPHABET)]
+        alphabet: String,
+
+        #[arg(short = 'l', long = "length", help = "The number of random characters the body must have", requires = "prefix")]
+        length: Option<usize>,
+
+        /// The token to verify
+        token: String
     }
 }
 
+#[derive(Subcommand)]
+pub enum ListCommands {
+    /// List the built-in wordlists, with the size of each list and the entropy of each word
+    Wordlists
+}
+
 #[derive(Subcommand)]
 pub enum TimestampCommands {
     /// Create a timestamp using the UTC timezone
@@ -192,6 +720,16 @@ pub enum TimestampCommands {
     Local
 }
 
+#[derive(Args)]
+#[group(multiple = true)]
+pub struct EntropyOptions {
+    #[arg(long = "entropy", help = "Report the entropy of the generated data", global = true)]
+    pub report: bool,
+
+    #[arg(long = "min-bits", help = "Refuse to generate passwords, passphrases or bytes with less entropy than this", default_value = "32", global = true)]
+    pub min_bits: usize
+}
+
 #[derive(Args)]
 #[group(multiple = true)]
 pub struct LengthRange {
@@ -215,6 +753,175 @@ pub struct ModelParameters {
     pub backoff: bool
 }
 
+#[derive(Args)]
+#[group(multiple = true)]
+pub struct Composition {
+    #[arg(long = "min-upper", help = "The minimum number of uppercase letters", default_value = "0")]
+    pub min_upper: usize,
+
+    #[arg(long = "min-lower", help = "The minimum number of lowercase letters", default_value = "0")]
+    pub min_lower: usize,
+
+    #[arg(long = "min-digits", help = "The minimum number of digits", default_value = "0")]
+    pub min_digits: usize,
+
+    #[arg(long = "min-symbols", help = "The minimum number of symbols", default_value = "0")]
+    pub min_symbols: usize,
+
+    #[arg(short = 'a', long = "allow", help = "Additional characters to include in the character set", default_value = "")]
+    pub allow: String,
+
+    #[arg(short = 'f', long = "forbid", help = "Characters to exclude from the character set", default_value = "")]
+    pub forbid: String,
+
+    #[arg(short = 'L', long = "no-lookalikes", help = "Exclude characters which are easily mistaken for one another, such as 0O1lI")]
+    pub no_lookalikes: bool,
+
+    #[arg(long = "max-repeat", help = "The longest permitted run of identical characters")]
+    pub max_repeat: Option<usize>,
+
+    #[arg(long = "max-sequence", help = "The longest permitted run of sequential characters, such as abc or 321")]
+    pub max_sequence: Option<usize>
+}
+
+#[derive(Args)]
+#[group(multiple = true)]
+pub struct CustomCharsets {
+    #[arg(short = '1', long = "custom-charset1", help = "A custom character set referenced by ?1 in the mask")]
+    pub charset1: Option<String>,
+
+    #[arg(short = '2', long = "custom-charset2", help = "A custom character set referenced by ?2 in the mask")]
+    pub charset2: Option<String>,
+
+    #[arg(short = '3', long = "custom-charset3", help = "A custom character set referenced by ?3 in the mask")]
+    pub charset3: Option<String>,
+
+    #[arg(short = '4', long = "custom-charset4", help = "A custom character set referenced by ?4 in the mask")]
+    pub charset4: Option<String>
+}
+
+#[derive(Args)]
+#[group(multiple = true)]
+pub struct PassphraseTransforms {
+    #[arg(value_enum, short = 'c', long = "capitalize", help = "How to capitalize the words", default_value = "lower")]
+    pub capitalization: Capitalization,
+
+    #[arg(long = "digit", help = "Insert a random digit at a random word boundary")]
+    pub digit: bool,
+
+    #[arg(long = "symbol", help = "Insert a random symbol at a random word boundary")]
+    pub symbol: bool,
+
+    #[arg(long = "separators", help = "Separate words with characters drawn at random from this set", default_value = "", allow_hyphen_values = true)]
+    pub separators: String,
+
+    #[arg(long = "leet", help = "Replace letters with their leetspeak equivalents at random")]
+    pub leet: bool,
+
+    #[arg(short = 'u', long = "unique", help = "Never use the same word twice")]
+    pub unique: bool
+}
+
+#[derive(Args)]
+#[group(multiple = false)]
+pub struct PhysicalRandomness {
+    #[arg(long = "dice", help = "Choose words from a built-in wordlist with dice rolls, such as \"41524 11263\"", conflicts_with_all = ["path", "count", "bits_count"])]
+    pub dice: Option<String>,
+
+    #[arg(long = "coins", help = "Choose words from a built-in wordlist with coin flips, such as \"HTTHHTHT\"", conflicts_with_all = ["path", "count", "bits_count"])]
+    pub coins: Option<String>,
+
+    #[arg(long = "cards", help = "Choose words from a built-in wordlist with a shuffled deck of cards, such as \"AS 7H TD QC\"", conflicts_with_all = ["path", "count", "bits_count"])]
+    pub cards: Option<String>
+}
+
+#[derive(Args)]
+#[group(multiple = true)]
+pub struct OtpOptions {
+    #[arg(value_enum, long = "algorithm", help = "The hash function used to compute codes", default_value = "sha1")]
+    pub algorithm: OtpAlgorithm,
+
+    #[arg(long = "digits", help = "The number of digits in each code", default_value = "6", value_parser = clap::value_parser!(u32).range(6..=8))]
+    pub digits: u32,
+
+    #[arg(long = "period", help = "The number of seconds for which each TOTP code is valid", default_value = "30", value_parser = clap::value_parser!(u64).range(1..))]
+    pub period: u64
+}
+
+#[derive(Args)]
+#[group(multiple = true, conflicts_with = "verify")]
+pub struct HashCosts {
+    #[arg(long = "memory", help = "The memory used by Argon2id, in kibibytes [default: 19456]")]
+    pub memory: Option<u32>,
+
+    #[arg(long = "time", help = "The number of passes made by Argon2id [default: 2]")]
+    pub time: Option<u32>,
+
+    #[arg(long = "parallelism", help = "The number of lanes used by Argon2id, or the parallelization parameter of scrypt [default: 1]")]
+    pub parallelism: Option<u32>,
+
+    #[arg(long = "log-n", help = "The base two logarithm of the scrypt cost parameter N [default: 17]")]
+    pub log_n: Option<u8>,
+
+    #[arg(long = "block-size", help = "The block size parameter r of scrypt [default: 8]")]
+    pub block_size: Option<u32>,
+
+    #[arg(long = "iterations", help = "The number of iterations of PBKDF2 or rounds of sha512-crypt [default: 600000, 210000 or 5000]")]
+    pub iterations: Option<u32>,
+
+    #[arg(long = "cost", help = "The cost factor of bcrypt [default: 12]")]
+    pub cost: Option<u32>
+}
+
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+pub struct KeySources {
+    #[arg(short = 'k', long = "key-file", help = "A file containing a 32-byte key, such as one created by create bytes 32")]
+    pub key_file: Option<PathBuf>,
+
+    #[arg(short = 'P', long = "passphrase", help = "A passphrase which is stretched into a key with Argon2id")]
+    pub passphrase: Option<String>,
+
+    #[arg(long = "passphrase-file", help = "A file containing the passphrase, which keeps it out of the process list")]
+    pub passphrase_file: Option<PathBuf>
+}
+
+#[derive(Args)]
+#[group(multiple = true, requires = "encoding")]
+pub struct EncodingSettings {
+    #[arg(short = 'u', long = "uppercase", help = "Write hexadecimal or Bech32 in uppercase")]
+    pub uppercase: bool,
+
+    #[arg(long = "hrp", help = "The human-readable part of Bech32, such as \"age\"")]
+    pub hrp: Option<String>,
+
+    #[arg(long = "label", help = "The label of PEM armor [default: DATA]")]
+    pub label: Option<String>
+}
+
+#[derive(Args)]
+#[group(multiple = true)]
+pub struct LiteralArgs {
+    #[arg(value_enum, long = "as", help = "Write the bytes as an array literal in this language")]
+    pub language: Option<Language>,
+
+    #[arg(long = "name", help = "The identifier of the array literal [default: key or KEY]", requires = "language")]
+    pub name: Option<String>,
+
+    #[arg(long = "width", help = "The number of bytes on each line of the array literal, or 0 for one line", default_value = "12", requires = "language")]
+    pub width: usize
+}
+
+#[derive(Args)]
+#[group(multiple = false)]
+pub struct SaltOptions {
+    #[arg(short = 's', long = "salt", help = "The salt, as text")]
+    pub salt: Option<String>,
+
+    #[arg(long = "salt-hex", help = "The salt, encoded as hexadecimal")]
+    pub salt_hex: Option<String>
+}
+
 #[derive(Args)]
 #[group(multiple = false)]
 pub struct CacheControl {
@@ -225,6 +932,18 @@ pub struct CacheControl {
     pub rebuild_cache: bool
 }
 
+impl From<&LiteralArgs> for LiteralOptions {
+    fn from(literal: &LiteralArgs) -> Self {
+        LiteralOptions { name: literal.name.clone(), width: literal.width }
+    }
+}
+
+impl From<EncodingSettings> for EncodingOptions {
+    fn from(settings: EncodingSettings) -> Self {
+        EncodingOptions { uppercase: settings.uppercase, hrp: settings.hrp, label: settings.label }
+    }
+}
+
 impl Verbosity {
     fn to_filter(&self) -> LevelFilter {
         if self.debug { LevelFilter::Trace }
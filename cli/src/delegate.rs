@@ -1,5 +1,6 @@
 use std::sync::mpsc::Sender;
 
+use lib::random::{Csprng, RandomSource};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 /**
@@ -12,15 +13,31 @@ pub fn create_serial(sender: Sender<Vec<u8>>, closure: impl FnOnce() -> Vec<u8>)
 }
 
 /**
- * Create a parallel buffer and send it to the [sender].
+ * The number of buffers created in parallel before they are sent to the [sender].
  */
-pub fn create_parallel(sender: Sender<Vec<u8>>, count: Option<usize>, closure: impl Fn() -> Vec<u8> + Send + Sync) {
+const CHUNK_SIZE: usize = 4096;
+
+/**
+ * Create buffers in parallel and send them to the [sender] in the order of their indices.
+ *
+ * Each buffer is created from its own stream of the [source], so that the output does not depend on scheduling.
+ */
+pub fn create_parallel(
+    sender: Sender<Vec<u8>>,
+    count: Option<usize>,
+    source: &RandomSource,
+    closure: impl Fn(usize, &mut Csprng) -> Vec<u8> + Send + Sync
+) {
     let max = count.unwrap_or(1);
-    let range = 0..max;
 
-    range.into_par_iter().for_each(|_| {
-        let buffer = closure();
+    for start in (0..max).step_by(CHUNK_SIZE) {
+        let range = start..max.min(start + CHUNK_SIZE);
+        let buffers = range.into_par_iter()
+            .map(|index| closure(index, &mut source.stream(index)))
+            .collect::<Vec<_>>();
 
-        sender.send(buffer).unwrap();
-    });
+        for buffer in buffers {
+            sender.send(buffer).unwrap();
+        }
+    }
 }
@@ -8,6 +8,7 @@ extern crate sha1;
 extern crate sha2;
 extern crate hex;
 extern crate rayon;
+extern crate num_bigint;
 
 mod config;
 mod delegate;
@@ -16,7 +17,7 @@ mod command;
 
 use config::{parse, setup_logging, Commands};
 use panic::setup_panic;
-use command::{handle_create_command, handle_analyze_command, handle_visualize_command};
+use command::{handle_create_command, handle_list_command, handle_hash_command, handle_crypt_command, handle_split_command, handle_combine_command, handle_mac_command, handle_derive_command, handle_encode_command, handle_decode_command, handle_verify_command, handle_analyze_command, handle_visualize_command};
 
 type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
 type UnitResult<'a> = Result<(), BoxedError<'a>>;
@@ -28,8 +29,19 @@ fn execute() -> UnitResult<'static> {
     setup_logging(&arguments.verbosity)?;
 
     match arguments.command {
-        Commands::Create { command } => handle_create_command(command)?,
-        Commands::Analyze { input } => handle_analyze_command(input)?,
+        Commands::Create { entropy, command } => handle_create_command(*command, &entropy, arguments.seed)?,
+        Commands::List { command } => handle_list_command(command)?,
+        Commands::Hash { algorithm, generate, verify, costs } => handle_hash_command(algorithm, generate, verify, costs, arguments.seed)?,
+        Commands::Encrypt { keys, output, input } => handle_crypt_command(true, keys, output, input)?,
+        Commands::Decrypt { keys, output, input } => handle_crypt_command(false, keys, output, input)?,
+        Commands::Split { threshold, shares, encoding, input } => handle_split_command(threshold, shares, encoding, input)?,
+        Commands::Combine { encoding, shares } => handle_combine_command(encoding, shares)?,
+        Commands::Mac { algorithm, key_file, verify, input } => handle_mac_command(algorithm, key_file, verify, input)?,
+        Commands::Derive { command } => handle_derive_command(command)?,
+        Commands::Encode { encoding, settings, literal, from, lenient, input } => handle_encode_command(encoding, settings, literal, from, lenient, input)?,
+        Commands::Decode { encoding, speak, lenient, hrp, label, input } => handle_decode_command(encoding, speak, lenient, hrp, label, input)?,
+        Commands::Verify { command } => handle_verify_command(command)?,
+        Commands::Analyze { pem, speak, input } => handle_analyze_command(pem, speak, input)?,
         Commands::Visualize { input, output } => handle_visualize_command(input, output)?
     };
 
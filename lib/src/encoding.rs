@@ -0,0 +1,752 @@
+use std::io::{ErrorKind, Read, Write};
+
+use base64::{alphabet, DecodeError as Base64Error, Engine};
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::general_purpose::{STANDARD, URL_SAFE};
+use bech32::{Bech32, Hrp};
+use clap::ValueEnum;
+use data_encoding::{BASE32, BASE32_NOPAD, Encoding as DataEncoding, Specification};
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type StringResult<'a> = Result<String, BoxedError<'a>>;
+type BytesResult<'a> = Result<Vec<u8>, BoxedError<'a>>;
+type LengthResult<'a> = Result<u64, BoxedError<'a>>;
+
+/// The alphabet of Ascii85, which is every character from `!` to `u`.
+const ASCII85_ALPHABET: &[u8; 85] = b"!\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstu";
+
+/// The alphabet of Z85, which avoids quotes and backslashes so it can be embedded in source code.
+const Z85_ALPHABET: &[u8; 85] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+
+/// The alphabet of Crockford's Base32, which leaves out I, L, O and U.
+const CROCKFORD_ALPHABET: &str = "0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// The alphabet of Bech32, which is matched without regard to case.
+const BECH32_ALPHABET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// The number of characters of Base64 on each line of PEM armor.
+const PEM_LINE_LENGTH: usize = 64;
+
+/// The number of bytes encoded at a time when streaming, which is a whole number of the groups of bytes that Base64,
+/// Base32 and Base85 encode separately.
+const ENCODE_CHUNK_SIZE: usize = 65520;
+
+/// The number of characters read at a time when decoding a stream.
+const DECODE_CHUNK_SIZE: usize = 65536;
+
+/// The label of PEM armor when none is given.
+pub const DEFAULT_PEM_LABEL: &str = "DATA";
+
+/// The ways bytes can be written as text.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum Encoding {
+    #[default]
+    Hex,
+    Base64,
+    /// Base64 with the URL-safe alphabet of RFC 4648.
+    #[value(name = "base64url")]
+    Base64Url,
+    /// Base32 as described by RFC 4648, padded with `=`.
+    Base32,
+    /// Base32 as described by RFC 4648, without padding.
+    #[value(name = "base32-nopad")]
+    Base32NoPad,
+    /// Crockford's Base32, which avoids letters that are easily confused.
+    Crockford,
+    /// Base58 with the alphabet used by Bitcoin.
+    Base58,
+    /// ZeroMQ's Base85, which needs a multiple of four bytes.
+    Z85,
+    /// Ascii85 as used by btoa and PostScript, without the `<~` and `~>` delimiters.
+    Ascii85,
+    /// Bech32 with a human-readable part, as described by BIP 173.
+    Bech32,
+    /// Base64 wrapped at 64 characters between PEM boundaries.
+    Pem
+}
+
+/// The settings which only some encodings use.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EncodingOptions {
+    /// Write hexadecimal or Bech32 in uppercase.
+    pub uppercase: bool,
+    /// The human-readable part of Bech32, which is required by it.
+    pub hrp: Option<String>,
+    /// The label of PEM armor, which is [DEFAULT_PEM_LABEL] when none is given.
+    pub label: Option<String>
+}
+
+impl Encoding {
+    /// Whether bytes can be encoded a chunk at a time, because each group of bytes is encoded on its own.
+    fn encodes_in_chunks(&self) -> bool {
+        !matches!(self, Encoding::Base58 | Encoding::Bech32 | Encoding::Pem)
+    }
+
+    /// The number of characters in each group that can be decoded on its own, for the encodings whose groups all
+    /// have the same length, which can be decoded a chunk at a time.
+    fn group_length(&self) -> Option<usize> {
+        match self {
+            Encoding::Hex => Some(2),
+            Encoding::Base64 | Encoding::Base64Url => Some(4),
+            Encoding::Base32 | Encoding::Base32NoPad | Encoding::Crockford => Some(8),
+            Encoding::Z85 => Some(5),
+            _ => None
+        }
+    }
+
+    /// The characters which lenient decoding ignores besides whitespace.
+    fn ignored_characters(&self) -> &'static [u8] {
+        match self {
+            Encoding::Crockford => b"-",
+            _ => b""
+        }
+    }
+}
+
+fn crockford() -> DataEncoding {
+    let mut specification = Specification::new();
+
+    specification.symbols.push_str(CROCKFORD_ALPHABET);
+
+    // The specification is fixed, so building it can't fail.
+    specification.encoding().unwrap()
+}
+
+/// Base32 which accepts lowercase letters and ignores the bits left over at the end, with no padding.
+fn lenient_base32(symbols: &str, confusable: (&str, &str)) -> DataEncoding {
+    let mut specification = Specification::new();
+    let lowercase = symbols.to_ascii_lowercase();
+
+    specification.symbols.push_str(symbols);
+    specification.check_trailing_bits = false;
+
+    for (from, to) in lowercase.chars().zip(symbols.chars()).filter(|(from, to)| from != to) {
+        specification.translate.from.push(from);
+        specification.translate.to.push(to);
+    }
+
+    specification.translate.from.push_str(confusable.0);
+    specification.translate.to.push_str(confusable.1);
+
+    // The specification is fixed, so building it can't fail.
+    specification.encoding().unwrap()
+}
+
+fn error_at(offset: usize, message: &str) -> BoxedError<'static> {
+    format!("{message} at byte offset {offset}").into()
+}
+
+/// The characters of some text which belong to an encoding, along with the offset of each in the text.
+struct Significant {
+    characters: Vec<u8>,
+    offsets: Vec<usize>,
+    end: usize
+}
+
+impl Significant {
+    /// Keep every character of the text, or leave out whitespace and the given characters when [lenient].
+    fn new(text: &[u8], start: usize, lenient: bool, ignored: &[u8]) -> Self {
+        let (offsets, characters) = text.iter()
+            .enumerate()
+            .filter(|(_, byte)| !lenient || !(byte.is_ascii_whitespace() || ignored.contains(byte)))
+            .map(|(index, &byte)| (start + index, byte))
+            .unzip();
+
+        Significant { characters, offsets, end: start + text.len() }
+    }
+
+    /// The offset in the text of the character at [index], or the end of the text if there is no such character.
+    fn offset(&self, index: usize) -> usize {
+        self.offsets.get(index).copied().unwrap_or(self.end)
+    }
+
+    /// Leave out padding at the end.
+    fn trim_padding(&mut self) {
+        while self.characters.last() == Some(&b'=') {
+            self.characters.pop();
+            self.offsets.pop();
+        }
+    }
+}
+
+fn decode_hex<'a>(text: &Significant) -> BytesResult<'a> {
+    // Invalid digits are looked for first, since they are more useful to report than an odd length.
+    if let Some(index) = text.characters.iter().position(|byte| !byte.is_ascii_hexdigit()) {
+        return Err(error_at(text.offset(index), "Invalid hexadecimal digit"));
+    }
+
+    hex::decode(&text.characters).map_err(|_| error_at(text.end, "The hexadecimal ends in the middle of a byte"))
+}
+
+fn decode_base64<'a>(text: &Significant, url_safe: bool, lenient: bool) -> BytesResult<'a> {
+    let alphabet = if url_safe { &alphabet::URL_SAFE } else { &alphabet::STANDARD };
+    let config = match lenient {
+        true => GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent).with_decode_allow_trailing_bits(true),
+        false => GeneralPurposeConfig::new()
+    };
+
+    GeneralPurpose::new(alphabet, config).decode(&text.characters).map_err(|error| match error {
+        Base64Error::InvalidByte(index, _) => error_at(text.offset(index), "Invalid Base64 character"),
+        Base64Error::InvalidLastSymbol(index, _) => error_at(text.offset(index), "The last Base64 character has bits set which aren't used"),
+        Base64Error::InvalidLength(_) => error_at(text.end, "The Base64 ends in the middle of a byte"),
+        Base64Error::InvalidPadding => match text.characters.iter().position(|&byte| byte == b'=') {
+            Some(index) => error_at(text.offset(index), "Invalid Base64 padding"),
+            None => error_at(text.end, "The Base64 is missing its padding")
+        }
+    })
+}
+
+fn decode_data_encoding<'a>(text: &Significant, encoding: &DataEncoding, name: &str) -> BytesResult<'a> {
+    encoding.decode(&text.characters).map_err(|error| {
+        let message = match error.kind {
+            data_encoding::DecodeKind::Symbol => format!("Invalid {name} character"),
+            data_encoding::DecodeKind::Trailing => format!("The last {name} character has bits set which aren't used"),
+            data_encoding::DecodeKind::Padding => format!("Invalid {name} padding"),
+            _ => format!("The {name} ends in the middle of a byte")
+        };
+        let offset = match error.kind {
+            data_encoding::DecodeKind::Length => text.end,
+            _ => text.offset(error.position)
+        };
+
+        error_at(offset, &message)
+    })
+}
+
+fn decode_base58<'a>(text: &Significant) -> BytesResult<'a> {
+    bs58::decode(&text.characters).into_vec().map_err(|error| match error {
+        bs58::decode::Error::InvalidCharacter { index, .. } | bs58::decode::Error::NonAsciiCharacter { index } => {
+            error_at(text.offset(index), "Invalid Base58 character")
+        },
+        error => error.to_string().into()
+    })
+}
+
+/// Decode groups of five characters as four bytes, where a shorter group at the end is one byte fewer than its length.
+fn decode_base85<'a>(text: &Significant, alphabet: &[u8; 85], compressed_zeros: bool, name: &str) -> BytesResult<'a> {
+    let mut decoded = Vec::with_capacity(text.characters.len() / 5 * 4);
+    let mut index = 0;
+
+    while index < text.characters.len() {
+        if compressed_zeros && text.characters[index] == b'z' {
+            decoded.extend_from_slice(&[0; 4]);
+            index += 1;
+            continue;
+        }
+
+        let group = &text.characters[index..(index + 5).min(text.characters.len())];
+
+        if group.len() == 1 { return Err(error_at(text.offset(index), &format!("The {name} ends with a single character"))); }
+
+        let mut value: u64 = 0;
+
+        for position in 0..5 {
+            let digit = match group.get(position) {
+                Some(&character) => alphabet.iter()
+                    .position(|&symbol| symbol == character)
+                    .ok_or_else(|| error_at(text.offset(index + position), &format!("Invalid {name} character")))?,
+                // A short group is padded with the last character of the alphabet.
+                None => 84
+            };
+
+            value = value * 85 + digit as u64;
+        }
+
+        if value > u32::MAX as u64 { return Err(error_at(text.offset(index), &format!("The {name} group is too large"))); }
+
+        decoded.extend_from_slice(&(value as u32).to_be_bytes()[..group.len() - 1]);
+        index += group.len();
+    }
+
+    Ok(decoded)
+}
+
+fn decode_bech32<'a>(text: &Significant, options: &EncodingOptions) -> BytesResult<'a> {
+    let characters = &text.characters;
+    let separator = characters.iter().rposition(|&byte| byte == b'1').ok_or_else(|| error_at(text.offset(0), "The Bech32 has no separator"))?;
+    let uppercase = characters.iter().find(|byte| byte.is_ascii_alphabetic()).is_some_and(|byte| byte.is_ascii_uppercase());
+
+    for (index, byte) in characters.iter().enumerate() {
+        if byte.is_ascii_alphabetic() && byte.is_ascii_uppercase() != uppercase {
+            return Err(error_at(text.offset(index), "Bech32 can't mix uppercase and lowercase"));
+        }
+        if index > separator && !BECH32_ALPHABET.contains(&byte.to_ascii_lowercase()) {
+            return Err(error_at(text.offset(index), "Invalid Bech32 character"));
+        }
+    }
+
+    let string = String::from_utf8(characters.clone()).map_err(|error| error_at(text.offset(error.utf8_error().valid_up_to()), "Invalid Bech32 character"))?;
+    let (hrp, data) = bech32::decode(&string).map_err(|error| {
+        let index = characters.len().saturating_sub(6).max(separator + 1);
+
+        error_at(text.offset(index), &format!("Invalid Bech32 ({error})"))
+    })?;
+
+    if let Some(expected) = options.hrp.as_deref() {
+        if !hrp.as_str().eq_ignore_ascii_case(expected) {
+            return Err(error_at(text.offset(0), &format!("The human-readable part is {hrp}, not {expected}")));
+        }
+    }
+
+    Ok(data)
+}
+
+fn find(text: &[u8], pattern: &[u8], from: usize) -> Option<usize> {
+    text.get(from..)?.windows(pattern.len()).position(|window| window == pattern).map(|index| from + index)
+}
+
+fn decode_pem<'a>(text: &[u8], options: &EncodingOptions, lenient: bool) -> BytesResult<'a> {
+    const BEGIN: &[u8] = b"-----BEGIN ";
+    const END: &[u8] = b"-----END ";
+    const DASHES: &[u8] = b"-----";
+
+    let begin = find(text, BEGIN, 0).ok_or_else(|| error_at(0, "No PEM boundary was found"))?;
+
+    if !lenient && begin != 0 { return Err(error_at(0, "Text before the PEM boundary")); }
+
+    let label_start = begin + BEGIN.len();
+    let label_end = find(text, DASHES, label_start).ok_or_else(|| error_at(label_start, "The PEM boundary isn't closed"))?;
+    let label = &text[label_start..label_end];
+    let body_start = label_end + DASHES.len();
+    let end = find(text, END, body_start).ok_or_else(|| error_at(text.len(), "The PEM armor has no end boundary"))?;
+    let closing = [END, label, DASHES].concat();
+
+    if text.get(end..end + closing.len()) != Some(&closing[..]) { return Err(error_at(end, "The PEM end boundary has a different label")); }
+    if !lenient && end + closing.len() != text.len() { return Err(error_at(end + closing.len(), "Text after the PEM boundary")); }
+
+    if let Some(expected) = options.label.as_deref() {
+        if label != expected.as_bytes() {
+            return Err(error_at(label_start, &format!("The PEM label is {}, not {expected}", String::from_utf8_lossy(label))));
+        }
+    }
+
+    let body = &text[body_start..end];
+
+    if !lenient {
+        // Every line of Base64 is full except the last, and each boundary is on its own line.
+        let lines = match body {
+            b"\n" => &[][..],
+            _ => body.strip_prefix(b"\n")
+                .and_then(|body| body.strip_suffix(b"\n"))
+                .ok_or_else(|| error_at(body_start, "The PEM boundaries must be on their own lines"))?
+        };
+        let mut offset = body_start + 1;
+        let count = lines.split(|&byte| byte == b'\n').count();
+
+        for (number, line) in lines.split(|&byte| byte == b'\n').enumerate() {
+            if line.len() > PEM_LINE_LENGTH || (number + 1 < count && line.len() != PEM_LINE_LENGTH) {
+                return Err(error_at(offset, &format!("Each line of PEM armor must have {PEM_LINE_LENGTH} characters, except the last")));
+            }
+
+            offset += line.len() + 1;
+        }
+    }
+
+    let characters = Significant::new(body, body_start, true, &[]);
+
+    decode_base64(&characters, false, lenient)
+}
+
+/// Leave out a single line ending at the end of some text.
+fn strip_line_ending(text: &[u8]) -> &[u8] {
+    text.strip_suffix(b"\n").map(|text| text.strip_suffix(b"\r").unwrap_or(text)).unwrap_or(text)
+}
+
+/// Read text written in the given encoding.
+///
+/// Decoding is strict unless [lenient]: only the characters the encoding would write are accepted, along with a
+/// single line ending at the end. Lenient decoding ignores whitespace, missing padding and unused bits, accepts
+/// lowercase Base32 and Crockford's substitutions for confusable letters, and ignores text around PEM armor and
+/// the delimiters of Ascii85. Errors give the byte offset in the text at which they were found.
+pub fn decode_text<'a>(text: &[u8], encoding: Encoding, options: &EncodingOptions, lenient: bool) -> BytesResult<'a> {
+    decode_from(strip_line_ending(text), 0, encoding, options, lenient)
+}
+
+/// Read text which begins at [start] in the input, so that errors give offsets in the input.
+fn decode_from<'a>(text: &[u8], start: usize, encoding: Encoding, options: &EncodingOptions, lenient: bool) -> BytesResult<'a> {
+    let (text, start) = match (encoding, lenient) {
+        (Encoding::Ascii85, true) => {
+            let trimmed = text.trim_ascii();
+            let leading = text.len() - text.trim_ascii_start().len();
+            let inner = trimmed.strip_prefix(b"<~").map(|inner| inner.strip_suffix(b"~>").unwrap_or(inner));
+
+            match inner {
+                Some(inner) => (inner, start + leading + 2),
+                None => (text, start)
+            }
+        },
+        _ => (text, start)
+    };
+
+    let mut significant = Significant::new(text, start, lenient, encoding.ignored_characters());
+
+    match (encoding, lenient) {
+        (Encoding::Hex, _) => decode_hex(&significant),
+        (Encoding::Base64, _) => decode_base64(&significant, false, lenient),
+        (Encoding::Base64Url, _) => decode_base64(&significant, true, lenient),
+        (Encoding::Base32, false) => decode_data_encoding(&significant, &BASE32, "Base32"),
+        (Encoding::Base32NoPad, false) => decode_data_encoding(&significant, &BASE32_NOPAD, "Base32"),
+        (Encoding::Base32 | Encoding::Base32NoPad, true) => {
+            significant.trim_padding();
+
+            decode_data_encoding(&significant, &lenient_base32(&BASE32_NOPAD.specification().symbols, ("", "")), "Base32")
+        },
+        (Encoding::Crockford, false) => decode_data_encoding(&significant, &crockford(), "Crockford Base32"),
+        (Encoding::Crockford, true) => decode_data_encoding(&significant, &lenient_base32(CROCKFORD_ALPHABET, ("ILOilo", "110110")), "Crockford Base32"),
+        (Encoding::Base58, _) => decode_base58(&significant),
+        (Encoding::Z85, _) => {
+            if !lenient && !significant.characters.len().is_multiple_of(5) {
+                return Err(error_at(significant.end, "Z85 must have a multiple of five characters"));
+            }
+
+            decode_base85(&significant, Z85_ALPHABET, false, "Z85")
+        },
+        (Encoding::Ascii85, _) => decode_base85(&significant, ASCII85_ALPHABET, true, "Ascii85"),
+        (Encoding::Bech32, _) => decode_bech32(&significant, options),
+        (Encoding::Pem, _) => decode_pem(text, options, lenient)
+    }
+}
+
+/// Reads until the buffer is full or the input ends, returning the number of bytes read.
+fn read_chunk<'a>(input: &mut impl Read, buffer: &mut [u8]) -> Result<usize, BoxedError<'a>> {
+    let mut length = 0;
+
+    while length < buffer.len() {
+        match input.read(&mut buffer[length..]) {
+            Ok(0) => break,
+            Ok(count) => length += count,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into())
+        }
+    }
+
+    Ok(length)
+}
+
+/// The length of the longest prefix of some text which holds only whole groups of the given length, leaving out
+/// whitespace and ignored characters when [lenient]. Padding may only come at the end, so the prefix stops before it.
+fn whole_groups(text: &[u8], group_length: usize, lenient: bool, ignored: &[u8]) -> usize {
+    let mut count = 0;
+    let mut length = 0;
+
+    for (index, &byte) in text.iter().enumerate() {
+        if byte == b'=' { break; }
+        if lenient && (byte.is_ascii_whitespace() || ignored.contains(&byte)) { continue; }
+
+        count += 1;
+
+        if count % group_length == 0 { length = index + 1; }
+    }
+
+    length
+}
+
+/// Decode everything read from the input, writing the bytes to the output as it goes, and return the number of
+/// bytes written.
+///
+/// Hexadecimal, Base64, Base32 and Z85 are decoded a chunk at a time, so the input never has to fit in memory,
+/// while the other encodings are decoded once all of the input has been read. Decoding is otherwise the same as
+/// [decode_text], but bytes may already have been written when an error is found later in the input.
+pub fn decode_stream<'a>(input: &mut impl Read, output: &mut impl Write, encoding: Encoding, options: &EncodingOptions, lenient: bool) -> LengthResult<'a> {
+    let group_length = match encoding.group_length() {
+        Some(group_length) => group_length,
+        None => {
+            let mut text = Vec::new();
+
+            input.read_to_end(&mut text)?;
+
+            let bytes = decode_text(&text, encoding, options, lenient)?;
+
+            output.write_all(&bytes)?;
+
+            return Ok(bytes.len() as u64);
+        }
+    };
+
+    let mut buffer = vec![0; DECODE_CHUNK_SIZE];
+    let mut pending = Vec::new();
+    let mut start = 0;
+    let mut total = 0;
+
+    loop {
+        let length = read_chunk(input, &mut buffer)?;
+
+        pending.extend_from_slice(&buffer[..length]);
+
+        if length < buffer.len() {
+            let bytes = decode_from(strip_line_ending(&pending), start, encoding, options, lenient)?;
+
+            output.write_all(&bytes)?;
+
+            return Ok(total + bytes.len() as u64);
+        }
+
+        // The last two characters are kept back, since they may be the line ending at the end of the input.
+        let end = whole_groups(&pending[..pending.len() - 2], group_length, lenient, encoding.ignored_characters());
+        let bytes = decode_from(&pending[..end], start, encoding, options, lenient)?;
+
+        output.write_all(&bytes)?;
+        pending.drain(..end);
+        start += end;
+        total += bytes.len() as u64;
+    }
+}
+
+/// Encode groups of four bytes as five characters, ending with a shorter group of as many characters as bytes plus one.
+fn encode_base85(bytes: &[u8], alphabet: &[u8; 85], compress_zeros: bool) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(4) * 5);
+
+    for chunk in bytes.chunks(4) {
+        let mut group = [0; 4];
+
+        group[..chunk.len()].copy_from_slice(chunk);
+
+        let mut value = u32::from_be_bytes(group);
+
+        if compress_zeros && chunk.len() == 4 && value == 0 {
+            encoded.push('z');
+            continue;
+        }
+
+        let mut digits = [0; 5];
+
+        for digit in digits.iter_mut().rev() {
+            *digit = alphabet[(value % 85) as usize];
+            value /= 85;
+        }
+
+        encoded.extend(digits[..=chunk.len()].iter().map(|&digit| digit as char));
+    }
+
+    encoded
+}
+
+/// Check the settings of an encoding, so that mistakes are reported before any bytes are generated.
+pub fn check_encoding_options<'a>(encoding: Encoding, options: &EncodingOptions) -> Result<(), BoxedError<'a>> {
+    match encoding {
+        Encoding::Bech32 => {
+            let hrp = options.hrp.as_deref().ok_or("Bech32 needs a human-readable part, which is given with --hrp")?;
+
+            Hrp::parse(hrp).map_err(|error| format!("{hrp} isn't a valid human-readable part: {error}"))?;
+        },
+        Encoding::Pem => {
+            let label = options.label.as_deref().unwrap_or(DEFAULT_PEM_LABEL);
+
+            // RFC 7468 allows hyphens and spaces inside a label, but not at either end.
+            let valid = label.bytes().all(|byte| byte.is_ascii_graphic() || byte == b' ')
+                && !label.starts_with(['-', ' '])
+                && !label.ends_with(['-', ' ']);
+
+            if label.is_empty() || !valid {
+                return Err(format!("{label} isn't a valid PEM label").into());
+            }
+        },
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// Write bytes as text in the given encoding.
+pub fn encode_bytes<'a>(bytes: &[u8], encoding: Encoding, options: &EncodingOptions) -> StringResult<'a> {
+    check_encoding_options(encoding, options)?;
+
+    let encoded = match encoding {
+        Encoding::Hex if options.uppercase => hex::encode_upper(bytes),
+        Encoding::Hex => hex::encode(bytes),
+        Encoding::Base64 => STANDARD.encode(bytes),
+        Encoding::Base64Url => URL_SAFE.encode(bytes),
+        Encoding::Base32 => BASE32.encode(bytes),
+        Encoding::Base32NoPad => BASE32_NOPAD.encode(bytes),
+        Encoding::Crockford => crockford().encode(bytes),
+        Encoding::Base58 => bs58::encode(bytes).into_string(),
+        Encoding::Z85 => {
+            if !bytes.len().is_multiple_of(4) { return Err("Z85 can only encode a multiple of four bytes".into()); }
+
+            encode_base85(bytes, Z85_ALPHABET, false)
+        },
+        Encoding::Ascii85 => encode_base85(bytes, ASCII85_ALPHABET, true),
+        Encoding::Bech32 => {
+            // The human-readable part was checked above.
+            let hrp = Hrp::parse(options.hrp.as_deref().unwrap()).unwrap();
+
+            match options.uppercase {
+                true => bech32::encode_upper::<Bech32>(hrp, bytes),
+                false => bech32::encode_lower::<Bech32>(hrp, bytes)
+            }.map_err(|_| "Bech32 can encode at most 1023 characters, so fewer bytes are needed")?
+        },
+        Encoding::Pem => {
+            let label = options.label.as_deref().unwrap_or(DEFAULT_PEM_LABEL);
+            let armor = pem::Pem::new(label, bytes);
+
+            pem::encode_config(&armor, pem::EncodeConfig::new().set_line_ending(pem::LineEnding::LF)).trim_end().to_string()
+        }
+    };
+
+    Ok(encoded)
+}
+
+/// Encode everything read from the input, writing the text to the output as it goes, and return the number of
+/// bytes read.
+///
+/// Every encoding but Base58, Bech32 and PEM is encoded a chunk at a time, so the input never has to fit in memory.
+/// Z85 needs a multiple of four bytes, which is only known to be missing once the rest has been written.
+pub fn encode_stream<'a>(input: &mut impl Read, output: &mut impl Write, encoding: Encoding, options: &EncodingOptions) -> LengthResult<'a> {
+    check_encoding_options(encoding, options)?;
+
+    if !encoding.encodes_in_chunks() {
+        let mut bytes = Vec::new();
+
+        input.read_to_end(&mut bytes)?;
+        output.write_all(encode_bytes(&bytes, encoding, options)?.as_bytes())?;
+
+        return Ok(bytes.len() as u64);
+    }
+
+    let mut buffer = vec![0; ENCODE_CHUNK_SIZE];
+    let mut total = 0;
+
+    loop {
+        let length = read_chunk(input, &mut buffer)?;
+
+        output.write_all(encode_bytes(&buffer[..length], encoding, options)?.as_bytes())?;
+        total += length as u64;
+
+        if length < buffer.len() { return Ok(total); }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(bytes: &[u8], encoding: Encoding) -> String {
+        encode_bytes(bytes, encoding, &EncodingOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn encodes_test_vectors() {
+        assert_eq!(encode(b"foobar", Encoding::Hex), "666f6f626172");
+        assert_eq!(encode(b"foob", Encoding::Base64), "Zm9vYg==");
+        assert_eq!(encode(&[0xfb, 0xff], Encoding::Base64Url), "-_8=");
+        assert_eq!(encode(b"foobar", Encoding::Base32), "MZXW6YTBOI======");
+        assert_eq!(encode(b"foobar", Encoding::Base32NoPad), "MZXW6YTBOI");
+        assert_eq!(encode(b"foobar", Encoding::Crockford), "CSQPYRK1E8");
+        assert_eq!(encode(b"Hello World!", Encoding::Base58), "2NEpo7TZRRrLZSi2U");
+        assert_eq!(encode(&[0x86, 0x4f, 0xd2, 0x6f, 0xb5, 0x59, 0xf7, 0x5b], Encoding::Z85), "HelloWorld");
+        assert_eq!(encode(b"Man is distinguished", Encoding::Ascii85), "9jqo^BlbD-BleB1DJ+*+F(f,q");
+        assert_eq!(encode(&[0, 0, 0, 0, 1], Encoding::Ascii85), "z!<");
+        assert_eq!(encode(b"\x01\x02", Encoding::Pem), "-----BEGIN DATA-----\nAQI=\n-----END DATA-----");
+    }
+
+    #[test]
+    fn encodes_bech32_with_hrp() {
+        let options = EncodingOptions { uppercase: false, hrp: Some("test".to_string()), label: None };
+        let encoded = encode_bytes(&[0x00, 0x14, 0x75], Encoding::Bech32, &options).unwrap();
+        let (hrp, data) = bech32::decode(&encoded).unwrap();
+
+        assert!(encoded.starts_with("test1"));
+        assert_eq!(hrp.as_str(), "test");
+        assert_eq!(data, [0x00, 0x14, 0x75]);
+        assert!(encode_bytes(&[0], Encoding::Bech32, &EncodingOptions::default()).is_err());
+        assert!(encode_bytes(&[0; 1024], Encoding::Bech32, &options).is_err());
+    }
+
+    fn decode(text: &str, encoding: Encoding, lenient: bool) -> Result<Vec<u8>, String> {
+        decode_text(text.as_bytes(), encoding, &EncodingOptions::default(), lenient).map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn decodes_what_it_encodes() {
+        let bytes = (0..=255).collect::<Vec<u8>>();
+        let options = EncodingOptions { uppercase: true, hrp: Some("test".to_string()), label: None };
+
+        for encoding in Encoding::value_variants() {
+            let length = if *encoding == Encoding::Bech32 { 128 } else { bytes.len() };
+
+            for end in [0, 1, 2, 3, 4, length] {
+                let bytes = &bytes[..end];
+
+                if *encoding == Encoding::Z85 && end % 4 != 0 { continue; }
+
+                let encoded = encode_bytes(bytes, *encoding, &options).unwrap();
+
+                assert_eq!(decode_text(encoded.as_bytes(), *encoding, &options, false).unwrap(), bytes, "{encoding:?}");
+                assert_eq!(decode_text(format!("{encoded}\n").as_bytes(), *encoding, &options, true).unwrap(), bytes, "{encoding:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn reports_offsets_of_errors() {
+        assert_eq!(decode("00ff0g", Encoding::Hex, false).unwrap_err(), "Invalid hexadecimal digit at byte offset 5");
+        assert_eq!(decode("00 ff 0g", Encoding::Hex, true).unwrap_err(), "Invalid hexadecimal digit at byte offset 7");
+        assert_eq!(decode("00 ff", Encoding::Hex, false).unwrap_err(), "Invalid hexadecimal digit at byte offset 2");
+        assert_eq!(decode("00f", Encoding::Hex, false).unwrap_err(), "The hexadecimal ends in the middle of a byte at byte offset 3");
+        assert_eq!(decode("Zm9v\nYm*y", Encoding::Base64, true).unwrap_err(), "Invalid Base64 character at byte offset 7");
+        assert_eq!(decode("MZXW6YTBO!", Encoding::Base32NoPad, false).unwrap_err(), "Invalid Base32 character at byte offset 9");
+        assert_eq!(decode("2NEpo7TZRRrLZSi2l", Encoding::Base58, false).unwrap_err(), "Invalid Base58 character at byte offset 16");
+        assert_eq!(decode("Hello~orld", Encoding::Z85, false).unwrap_err(), "Invalid Z85 character at byte offset 5");
+        assert!(decode("test1qqqqqqqq", Encoding::Bech32, false).unwrap_err().ends_with("at byte offset 7"));
+        assert_eq!(decode("test1qqbqqqqqq", Encoding::Bech32, false).unwrap_err(), "Invalid Bech32 character at byte offset 7");
+    }
+
+    #[test]
+    fn decodes_leniently() {
+        assert_eq!(decode("Zm9vYg", Encoding::Base64, true).unwrap(), b"foob");
+        assert!(decode("Zm9vYg", Encoding::Base64, false).is_err());
+        assert_eq!(decode("mzxw6ytboi======", Encoding::Base32, true).unwrap(), b"foobar");
+        assert!(decode("mzxw6ytboi======", Encoding::Base32, false).is_err());
+        assert_eq!(decode("csqp-yrk1-e8", Encoding::Crockford, true).unwrap(), b"foobar");
+        assert_eq!(decode("CSQPYRKIE8", Encoding::Crockford, true).unwrap(), b"foobar");
+        assert!(decode("CSQPYRK1EB", Encoding::Crockford, false).is_err());
+        assert_eq!(decode("<~9jqo^BlbD-BleB1DJ+*+F(f,q~>", Encoding::Ascii85, true).unwrap(), b"Man is distinguished");
+        assert_eq!(decode("Header\n-----BEGIN DATA-----\nAQI\n-----END DATA-----\nTrailer", Encoding::Pem, true).unwrap(), [1, 2]);
+        assert!(decode("Header\n-----BEGIN DATA-----\nAQI=\n-----END DATA-----", Encoding::Pem, false).is_err());
+        assert!(decode("-----BEGIN DATA-----\nAQI=\n-----END KEY-----", Encoding::Pem, true).is_err());
+    }
+
+    #[test]
+    fn streams_across_chunks() {
+        let bytes = (0..2 * ENCODE_CHUNK_SIZE + 17).map(|index| (index * 7 % 251) as u8).collect::<Vec<_>>();
+        let options = EncodingOptions::default();
+
+        for encoding in Encoding::value_variants().iter().filter(|encoding| encoding.encodes_in_chunks()) {
+            let bytes = match encoding {
+                Encoding::Z85 => &bytes[..bytes.len() / 4 * 4],
+                _ => &bytes
+            };
+            let mut encoded = Vec::new();
+            let mut decoded = Vec::new();
+
+            encode_stream(&mut &bytes[..], &mut encoded, *encoding, &options).unwrap();
+
+            assert_eq!(encoded, encode_bytes(bytes, *encoding, &options).unwrap().as_bytes(), "{encoding:?}");
+
+            // Wrapping the text in lines makes the groups straddle the chunks when decoding leniently.
+            let wrapped = encoded.chunks(76).collect::<Vec<_>>().join(&b"\r\n"[..]);
+
+            decode_stream(&mut &wrapped[..], &mut decoded, *encoding, &options, true).unwrap();
+
+            assert_eq!(decoded, bytes, "{encoding:?}");
+        }
+
+        let mut text = "00".repeat(DECODE_CHUNK_SIZE).into_bytes();
+
+        text[DECODE_CHUNK_SIZE + 1] = b'g';
+
+        let error = decode_stream(&mut &text[..], &mut Vec::new(), Encoding::Hex, &options, false).unwrap_err();
+
+        assert_eq!(error.to_string(), format!("Invalid hexadecimal digit at byte offset {}", DECODE_CHUNK_SIZE + 1));
+        assert!(decode_stream(&mut &b"AA==AA==\n"[..], &mut Vec::new(), Encoding::Base64, &options, false).is_err());
+        assert_eq!(encode_stream(&mut &b""[..], &mut Vec::new(), Encoding::Base64, &options).unwrap(), 0);
+    }
+
+    #[test]
+    fn rejects_invalid_options() {
+        let label = EncodingOptions { label: Some("-BAD-".to_string()), ..EncodingOptions::default() };
+
+        assert!(encode_bytes(&[0; 3], Encoding::Z85, &EncodingOptions::default()).is_err());
+        assert!(encode_bytes(&[0; 3], Encoding::Pem, &label).is_err());
+    }
+}
@@ -0,0 +1,421 @@
+use std::io::{ErrorKind, Read, Write};
+use std::mem::swap;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305, XNonce};
+use chacha20poly1305::aead::{Aead, Payload};
+use rand::{CryptoRng, Rng};
+
+use crate::generators::generate_bytes;
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type LengthResult<'a> = Result<u64, BoxedError<'a>>;
+type HeaderResult<'a> = Result<(Header, Vec<u8>), BoxedError<'a>>;
+type KeyResult<'a> = Result<[u8; KEY_SIZE], BoxedError<'a>>;
+
+/// The bytes which begin every encrypted file.
+const MAGIC: &[u8; 10] = b"CIPHERTOOL";
+
+/// The version of the format written by this build, which is the only one it can read.
+const VERSION: u8 = 1;
+
+/// The number of bytes in a key.
+const KEY_SIZE: usize = 32;
+
+/// The number of bytes in the salt used to stretch a passphrase.
+const SALT_SIZE: usize = 16;
+
+/// The number of bytes in the authentication tag which follows each chunk.
+const TAG_SIZE: usize = 16;
+
+/// The number of random bytes at the start of each nonce. The rest holds the index of the chunk and a flag for the last chunk.
+const NONCE_PREFIX_SIZE: usize = 19;
+
+/// The number of bytes of plaintext in every chunk except the last.
+const CHUNK_SIZE: u32 = 65536;
+
+/// The smallest and largest chunk sizes a header may give, which keep a forged header from exhausting memory.
+const CHUNK_SIZE_LIMITS: (u32, u32) = (1024, 16 * 1024 * 1024);
+
+/// The Argon2id parameters used to stretch passphrases, which are the second recommendation of RFC 9106.
+const ARGON2_PARAMETERS: Argon2Parameters = Argon2Parameters { memory: 65536, time: 3, parallelism: 4 };
+
+/// The most memory, in kibibytes, and the most passes that a header may ask Argon2id to use, which are four times
+/// the parameters used to encrypt. The header is only authenticated once the key is derived, so a forged header
+/// could otherwise make decryption use gibibytes of memory.
+const ARGON2_LIMITS: (u32, u32) = (4 * ARGON2_PARAMETERS.memory, 4 * ARGON2_PARAMETERS.time);
+
+/// The secret from which the key that encrypts a file is obtained.
+pub enum KeySource {
+    /// A random key, such as one created by `create bytes 32`.
+    Key([u8; KEY_SIZE]),
+    /// A passphrase, which is stretched into a key with Argon2id.
+    Passphrase(Vec<u8>)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Argon2Parameters {
+    memory: u32,
+    time: u32,
+    parallelism: u32
+}
+
+/// How the key of a file is derived from its key source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum KeyDerivation {
+    Key,
+    Argon2id { parameters: Argon2Parameters, salt: [u8; SALT_SIZE] }
+}
+
+/// The header of an encrypted file, which is authenticated along with every chunk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Header {
+    derivation: KeyDerivation,
+    chunk_size: u32,
+    nonce_prefix: [u8; NONCE_PREFIX_SIZE]
+}
+
+impl Header {
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+
+        bytes.push(VERSION);
+
+        match &self.derivation {
+            KeyDerivation::Key => bytes.push(0),
+            KeyDerivation::Argon2id { parameters, salt } => {
+                bytes.push(1);
+                bytes.extend_from_slice(&parameters.memory.to_be_bytes());
+                bytes.extend_from_slice(&parameters.time.to_be_bytes());
+                bytes.extend_from_slice(&parameters.parallelism.to_be_bytes());
+                bytes.extend_from_slice(salt);
+            }
+        }
+
+        bytes.extend_from_slice(&self.chunk_size.to_be_bytes());
+        bytes.extend_from_slice(&self.nonce_prefix);
+
+        bytes
+    }
+
+    /// Reads a header, returning it together with the bytes it was read from.
+    fn read<'a>(input: &mut impl Read) -> HeaderResult<'a> {
+        let mut bytes = vec![0; MAGIC.len() + 2];
+
+        read_exact(input, &mut bytes)?;
+
+        if &bytes[..MAGIC.len()] != MAGIC { return Err("The input was not encrypted by CipherTool".into()); }
+        if bytes[MAGIC.len()] != VERSION { return Err(format!("Version {} of the encrypted format is not supported", bytes[MAGIC.len()]).into()); }
+
+        let derivation = match bytes[MAGIC.len() + 1] {
+            0 => KeyDerivation::Key,
+            1 => {
+                let mut fields = [0; 12 + SALT_SIZE];
+
+                read_exact(input, &mut fields)?;
+                bytes.extend_from_slice(&fields);
+
+                let parameters = Argon2Parameters {
+                    memory: u32::from_be_bytes(fields[0..4].try_into()?),
+                    time: u32::from_be_bytes(fields[4..8].try_into()?),
+                    parallelism: u32::from_be_bytes(fields[8..12].try_into()?)
+                };
+
+                if parameters.memory > ARGON2_LIMITS.0 || parameters.time > ARGON2_LIMITS.1 {
+                    return Err(format!(
+                        "The header asks Argon2id to use {} KiB of memory and {} passes, which is more than the limits of {} KiB and {} passes",
+                        parameters.memory,
+                        parameters.time,
+                        ARGON2_LIMITS.0,
+                        ARGON2_LIMITS.1
+                    ).into());
+                }
+
+                KeyDerivation::Argon2id { parameters, salt: fields[12..].try_into()? }
+            },
+            value => return Err(format!("Key derivation method {value} is not supported").into())
+        };
+
+        let mut fields = [0; 4 + NONCE_PREFIX_SIZE];
+
+        read_exact(input, &mut fields)?;
+        bytes.extend_from_slice(&fields);
+
+        let chunk_size = u32::from_be_bytes(fields[0..4].try_into()?);
+
+        if chunk_size < CHUNK_SIZE_LIMITS.0 || chunk_size > CHUNK_SIZE_LIMITS.1 {
+            return Err(format!("The header gives a chunk size of {chunk_size} bytes, which is not allowed").into());
+        }
+
+        Ok((Header { derivation, chunk_size, nonce_prefix: fields[4..].try_into()? }, bytes))
+    }
+}
+
+/// Reads exactly enough bytes to fill the buffer, treating a short read as a truncated header.
+fn read_exact<'a>(input: &mut impl Read, buffer: &mut [u8]) -> Result<(), BoxedError<'a>> {
+    match input.read_exact(buffer) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => Err("The header of the encrypted data is truncated".into()),
+        Err(error) => Err(error.into())
+    }
+}
+
+/// Reads until the buffer is full or the input ends, returning the number of bytes read.
+fn read_chunk<'a>(input: &mut impl Read, buffer: &mut [u8]) -> LengthResult<'a> {
+    let mut length = 0;
+
+    while length < buffer.len() {
+        match input.read(&mut buffer[length..]) {
+            Ok(0) => break,
+            Ok(count) => length += count,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error.into())
+        }
+    }
+
+    Ok(length as u64)
+}
+
+/// Builds the nonce of a chunk from the random prefix, the index of the chunk and whether it's the last one.
+///
+/// Marking the last chunk means that removing chunks from the end of a file, or adding them, is detected.
+fn chunk_nonce(prefix: &[u8; NONCE_PREFIX_SIZE], index: u32, last: bool) -> XNonce {
+    let mut nonce = XNonce::default();
+
+    nonce[..NONCE_PREFIX_SIZE].copy_from_slice(prefix);
+    nonce[NONCE_PREFIX_SIZE..NONCE_PREFIX_SIZE + 4].copy_from_slice(&index.to_be_bytes());
+    nonce[NONCE_PREFIX_SIZE + 4] = last as u8;
+
+    nonce
+}
+
+fn derive_key<'a>(source: &KeySource, derivation: &KeyDerivation) -> KeyResult<'a> {
+    match (source, derivation) {
+        (KeySource::Key(key), KeyDerivation::Key) => Ok(*key),
+        (KeySource::Passphrase(passphrase), KeyDerivation::Argon2id { parameters, salt }) => {
+            let params = Params::new(parameters.memory, parameters.time, parameters.parallelism, Some(KEY_SIZE))
+                .map_err(|error| error.to_string())?;
+            let mut key = [0; KEY_SIZE];
+
+            Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                .hash_password_into(passphrase, salt, &mut key)
+                .map_err(|error| error.to_string())?;
+
+            Ok(key)
+        },
+        (KeySource::Key(_), _) => Err("The data was encrypted with a passphrase, but a key was given".into()),
+        (KeySource::Passphrase(_), _) => Err("The data was encrypted with a key, but a passphrase was given".into())
+    }
+}
+
+/// Reads a key created by `create bytes 32`, or by `create hex 32` or `create base64 32`.
+pub fn parse_key<'a>(bytes: &[u8]) -> KeyResult<'a> {
+    if let Ok(key) = bytes.try_into() { return Ok(key); }
+
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim();
+    let decoded = hex::decode(text).ok().or_else(|| {
+        use base64::Engine;
+
+        base64::engine::general_purpose::STANDARD.decode(text).ok()
+    });
+
+    match decoded.map(|key| key.try_into()) {
+        Some(Ok(key)) => Ok(key),
+        _ => Err(format!("A key must be {KEY_SIZE} bytes long, either raw or encoded as hexadecimal or Base64").into())
+    }
+}
+
+fn encrypt_with<'a, R: Rng + CryptoRng + Sized>(
+    input: &mut impl Read,
+    output: &mut impl Write,
+    source: &KeySource,
+    argon2: Argon2Parameters,
+    chunk_size: u32,
+    rng: &mut R
+) -> LengthResult<'a> {
+    let derivation = match source {
+        KeySource::Key(_) => KeyDerivation::Key,
+        KeySource::Passphrase(_) => KeyDerivation::Argon2id { parameters: argon2, salt: generate_bytes(SALT_SIZE, rng).try_into().unwrap() }
+    };
+    let header = Header { derivation, chunk_size, nonce_prefix: generate_bytes(NONCE_PREFIX_SIZE, rng).try_into().unwrap() };
+    let key = derive_key(source, &header.derivation)?;
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+    let encoded = header.encode();
+
+    output.write_all(&encoded)?;
+
+    let mut current = vec![0; chunk_size as usize];
+    let mut next = vec![0; chunk_size as usize];
+    let mut length = read_chunk(input, &mut current)?;
+    let mut total = 0;
+    let mut index: u32 = 0;
+
+    loop {
+        // A chunk is only known to be the last once the input has been found to end after it.
+        let following = if length == chunk_size as u64 { read_chunk(input, &mut next)? } else { 0 };
+        let last = following == 0;
+        let payload = Payload { msg: &current[..length as usize], aad: &encoded };
+        let ciphertext = cipher.encrypt(&chunk_nonce(&header.nonce_prefix, index, last), payload)
+            .map_err(|_| "The data could not be encrypted")?;
+
+        output.write_all(&ciphertext)?;
+        total += length;
+
+        if last { break; }
+
+        swap(&mut current, &mut next);
+        length = following;
+        index = index.checked_add(1).ok_or("The input is too large to encrypt")?;
+    }
+
+    output.flush()?;
+
+    Ok(total)
+}
+
+/// Encrypt a stream with XChaCha20-Poly1305, returning the number of bytes encrypted.
+///
+/// The input is split into chunks which are sealed one at a time, so it never has to fit in memory. Each chunk
+/// authenticates the header, its own position and whether it's the last chunk, so decryption detects any change
+/// to the header, and any chunk which is modified, reordered, removed or added.
+pub fn encrypt_stream<'a, R: Rng + CryptoRng + Sized>(input: &mut impl Read, output: &mut impl Write, source: &KeySource, rng: &mut R) -> LengthResult<'a> {
+    encrypt_with(input, output, source, ARGON2_PARAMETERS, CHUNK_SIZE, rng)
+}
+
+/// Decrypt a stream encrypted by [encrypt_stream], returning the number of bytes decrypted.
+///
+/// Each chunk is only written once it has been authenticated, and any failure is an error. Chunks before the
+/// failure will already have been written, so the output must be discarded if an error is returned.
+pub fn decrypt_stream<'a>(input: &mut impl Read, output: &mut impl Write, source: &KeySource) -> LengthResult<'a> {
+    let (header, encoded) = Header::read(input)?;
+    let key = derive_key(source, &header.derivation)?;
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+    let sealed_size = header.chunk_size as u64 + TAG_SIZE as u64;
+    let mut current = vec![0; sealed_size as usize];
+    let mut next = vec![0; sealed_size as usize];
+    let mut length = read_chunk(input, &mut current)?;
+    let mut total = 0;
+    let mut index: u32 = 0;
+
+    loop {
+        let following = if length == sealed_size { read_chunk(input, &mut next)? } else { 0 };
+        let last = following == 0;
+        let payload = Payload { msg: &current[..length as usize], aad: &encoded };
+        let plaintext = cipher.decrypt(&chunk_nonce(&header.nonce_prefix, index, last), payload)
+            .map_err(|_| "The data could not be decrypted, because the key is wrong or the data has been modified")?;
+
+        output.write_all(&plaintext)?;
+        total += plaintext.len() as u64;
+
+        if last { break; }
+
+        swap(&mut current, &mut next);
+        length = following;
+        index = index.checked_add(1).ok_or("The input has too many chunks")?;
+    }
+
+    output.flush()?;
+
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use super::*;
+
+    // Small parameters, so the tests run quickly and cross many chunk boundaries.
+    const TEST_ARGON2: Argon2Parameters = Argon2Parameters { memory: 8, time: 1, parallelism: 1 };
+    const TEST_CHUNK_SIZE: u32 = 1024;
+
+    /// The length of the header of data encrypted with a key.
+    const KEY_HEADER_SIZE: usize = 35;
+
+    fn encrypt(plaintext: &[u8], source: &KeySource) -> Vec<u8> {
+        let mut ciphertext = Vec::new();
+
+        encrypt_with(&mut &plaintext[..], &mut ciphertext, source, TEST_ARGON2, TEST_CHUNK_SIZE, &mut thread_rng()).unwrap();
+
+        ciphertext
+    }
+
+    fn decrypt<'a>(ciphertext: &[u8], source: &KeySource) -> Result<Vec<u8>, BoxedError<'a>> {
+        let mut plaintext = Vec::new();
+
+        decrypt_stream(&mut &ciphertext[..], &mut plaintext, source)?;
+
+        Ok(plaintext)
+    }
+
+    #[test]
+    fn round_trips_across_chunk_boundaries() {
+        let source = KeySource::Key([7; KEY_SIZE]);
+
+        for length in [0, 1, 1023, 1024, 1025, 2048, 3000] {
+            let plaintext = generate_bytes(length, &mut thread_rng());
+            let ciphertext = encrypt(&plaintext, &source);
+
+            assert_eq!(ciphertext.len(), KEY_HEADER_SIZE + length + length.div_ceil(1024).max(1) * TAG_SIZE);
+            assert_eq!(decrypt(&ciphertext, &source).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn round_trips_with_passphrase() {
+        let source = KeySource::Passphrase(b"correct horse battery staple".to_vec());
+        let ciphertext = encrypt(b"attack at dawn", &source);
+
+        assert_eq!(decrypt(&ciphertext, &source).unwrap(), b"attack at dawn");
+        assert!(decrypt(&ciphertext, &KeySource::Passphrase(b"correct horse".to_vec())).is_err());
+        assert!(decrypt(&ciphertext, &KeySource::Key([0; KEY_SIZE])).is_err());
+    }
+
+    #[test]
+    fn fails_closed_on_tampering() {
+        let source = KeySource::Key([7; KEY_SIZE]);
+        let plaintext = generate_bytes(2500, &mut thread_rng());
+        let ciphertext = encrypt(&plaintext, &source);
+
+        // Every bit of the header and the chunks is authenticated.
+        for position in 0..ciphertext.len() {
+            let mut modified = ciphertext.clone();
+
+            modified[position] ^= 0x01;
+
+            assert!(decrypt(&modified, &source).is_err(), "modifying byte {position} was not detected");
+        }
+
+        // Removing the last chunk, removing part of a chunk or adding data are all detected.
+        let sealed = TEST_CHUNK_SIZE as usize + TAG_SIZE;
+
+        assert!(decrypt(&ciphertext[..KEY_HEADER_SIZE + 2 * sealed], &source).is_err());
+        assert!(decrypt(&ciphertext[..ciphertext.len() - 1], &source).is_err());
+        assert!(decrypt(&[ciphertext.as_slice(), &[0]].concat(), &source).is_err());
+        assert!(decrypt(&ciphertext[..20], &source).is_err());
+    }
+
+    #[test]
+    fn refuses_expensive_headers() {
+        let header = |memory, time| Header {
+            derivation: KeyDerivation::Argon2id { parameters: Argon2Parameters { memory, time, parallelism: 4 }, salt: [0; SALT_SIZE] },
+            chunk_size: TEST_CHUNK_SIZE,
+            nonce_prefix: [0; NONCE_PREFIX_SIZE]
+        }.encode();
+
+        assert!(Header::read(&mut &header(ARGON2_LIMITS.0, ARGON2_LIMITS.1)[..]).is_ok());
+        assert!(Header::read(&mut &header(ARGON2_LIMITS.0 + 1, ARGON2_PARAMETERS.time)[..]).is_err());
+        assert!(Header::read(&mut &header(ARGON2_PARAMETERS.memory, ARGON2_LIMITS.1 + 1)[..]).is_err());
+    }
+
+    #[test]
+    fn parses_keys() {
+        let key = [0xab; KEY_SIZE];
+
+        assert_eq!(parse_key(&key).unwrap(), key);
+        assert_eq!(parse_key(format!("{}\n", hex::encode(key)).as_bytes()).unwrap(), key);
+        assert_eq!(parse_key(b"q6urq6urq6urq6urq6urq6urq6urq6urq6urq6urq6s=").unwrap(), key);
+        assert!(parse_key(b"too short").is_err());
+    }
+}
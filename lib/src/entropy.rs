@@ -0,0 +1,53 @@
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+
+/// Calculate the number of bits of entropy in a uniform choice from a keyspace of the given size.
+pub fn keyspace_bits(keyspace: &BigUint) -> f64 {
+    let bits = keyspace.bits();
+
+    // Keep the 64 most significant bits, which is more precision than an f64 can hold.
+    let shift = bits.saturating_sub(64);
+    let mantissa = (keyspace >> shift).to_f64().unwrap_or(0.0);
+
+    mantissa.log2() + shift as f64
+}
+
+/// Calculate the number of bits of entropy in [length] uniform choices from [choices] options.
+pub fn uniform_bits(choices: usize, length: usize) -> f64 {
+    length as f64 * (choices as f64).log2()
+}
+
+/// Find the shortest length whose entropy, as calculated by [entropy], is at least [bits].
+///
+/// The entropy must grow steadily with the length, so nothing is found if a single item carries no entropy.
+pub fn shortest_length(bits: usize, entropy: impl Fn(usize) -> f64) -> Option<usize> {
+    if entropy(1) <= 0.0 { return None; }
+
+    (1..).find(|&length| entropy(length) >= bits as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calculates_bits_of_small_keyspace() {
+        assert_eq!(keyspace_bits(&BigUint::from(1024_u32)), 10.0);
+    }
+
+    #[test]
+    fn calculates_bits_of_large_keyspace() {
+        let keyspace = BigUint::from(3_u32).pow(1000);
+        let expected = 1000.0 * 3_f64.log2();
+
+        assert!((keyspace_bits(&keyspace) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn finds_shortest_length() {
+        assert_eq!(shortest_length(128, |length| uniform_bits(256, length)), Some(16));
+        assert_eq!(shortest_length(129, |length| uniform_bits(256, length)), Some(17));
+        assert_eq!(shortest_length(77, |length| uniform_bits(7776, length)), Some(6));
+        assert_eq!(shortest_length(8, |length| uniform_bits(1, length)), None);
+    }
+}
@@ -1,60 +1,66 @@
-use base64::Engine;
-use rand::{Rng, thread_rng, distributions::Uniform};
-use hex::{encode, encode_upper};
-use base64::engine::general_purpose::{STANDARD, URL_SAFE};
+use rand::{Rng, distributions::Uniform};
 
-pub fn generate_bytes(length: usize) -> Vec<u8> {
+use crate::encoding::{encode_bytes, Encoding, EncodingOptions};
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type BytesResult<'a> = Result<Vec<u8>, BoxedError<'a>>;
+
+pub fn generate_bytes<R: Rng + Sized>(length: usize, rng: &mut R) -> Vec<u8> {
     if length == 0 { return Vec::<u8>::new(); }
 
     let range = Uniform::new_inclusive(u8::MIN, u8::MAX);
 
-    thread_rng()
-        .sample_iter(range)
+    rng.sample_iter(range)
         .take(length)
         .collect()
 }
 
-pub fn generate_hex(uppercase: bool, length: usize) -> Vec<u8> {
-    if length == 0 { return Vec::<u8>::new(); }
-
-    let bytes = generate_bytes(length);
+/// Generate random bytes and write them as text in the given encoding.
+pub fn generate_encoded<'a, R: Rng + Sized>(encoding: Encoding, options: &EncodingOptions, length: usize, rng: &mut R) -> BytesResult<'a> {
+    if length == 0 { return Ok(Vec::<u8>::new()); }
 
-    if uppercase { encode_upper(bytes).into_bytes() }
-    else { encode(bytes).into_bytes() }
+    Ok(encode_bytes(&generate_bytes(length, rng), encoding, options)?.into_bytes())
 }
 
-pub fn generate_base64(url_safe: bool, length: usize) -> Vec<u8> {
-    if length == 0 { return Vec::<u8>::new(); }
+pub fn generate_hex<R: Rng + Sized>(uppercase: bool, length: usize, rng: &mut R) -> Vec<u8> {
+    let options = EncodingOptions { uppercase, ..EncodingOptions::default() };
+
+    // Hexadecimal can encode any number of bytes, so this can't fail.
+    generate_encoded(Encoding::Hex, &options, length, rng).unwrap()
+}
 
-    let bytes = generate_bytes(length);
+pub fn generate_base64<R: Rng + Sized>(url_safe: bool, length: usize, rng: &mut R) -> Vec<u8> {
+    let encoding = if url_safe { Encoding::Base64Url } else { Encoding::Base64 };
 
-    if url_safe { URL_SAFE.encode(bytes).into_bytes() }
-    else { STANDARD.encode(bytes).into_bytes() }
+    // Base64 can encode any number of bytes, so this can't fail.
+    generate_encoded(encoding, &EncodingOptions::default(), length, rng).unwrap()
 }
 
 #[cfg(test)]
 mod tests {
     use std::str::from_utf8;
 
+    use rand::thread_rng;
+
     use super::*;
 
     #[test]
     fn generates_one_kilobyte() {
-        let bytes = generate_bytes(1024);
+        let bytes = generate_bytes(1024, &mut thread_rng());
 
         assert_eq!(bytes.len(), 1024)
     }
 
     #[test]
     fn generates_zero_bytes() {
-        let bytes = generate_bytes(0);
+        let bytes = generate_bytes(0, &mut thread_rng());
 
         assert_eq!(bytes.len(), 0)
     }
 
     #[test]
     fn generates_one_kilobyte_as_hex() {
-        let bytes = generate_hex(false, 1024);
+        let bytes = generate_hex(false, 1024, &mut thread_rng());
         let string = from_utf8(&bytes).unwrap();
 
         assert_eq!(string.chars().count(), 2048)
@@ -62,22 +68,31 @@ mod tests {
 
     #[test]
     fn generates_zero_bytes_as_hex() {
-        let bytes = generate_hex(false, 0);
+        let bytes = generate_hex(false, 0, &mut thread_rng());
 
         assert_eq!(bytes.len(), 0)
     }
 
     #[test]
     fn generates_one_kilobyte_as_base64() {
-        let bytes = generate_base64(false, 1024);
+        let bytes = generate_base64(false, 1024, &mut thread_rng());
         let string = from_utf8(&bytes).unwrap();
 
         assert_eq!(string.chars().count(), 1368)
     }
 
+    #[test]
+    fn generates_encoded_bytes() {
+        let bytes = generate_encoded(Encoding::Crockford, &EncodingOptions::default(), 5, &mut thread_rng()).unwrap();
+        let string = from_utf8(&bytes).unwrap();
+
+        assert_eq!(string.chars().count(), 8);
+        assert!(generate_encoded(Encoding::Z85, &EncodingOptions::default(), 5, &mut thread_rng()).is_err());
+    }
+
     #[test]
     fn generates_zero_bytes_as_base64() {
-        let bytes = generate_base64(false, 0);
+        let bytes = generate_base64(false, 0, &mut thread_rng());
 
         assert_eq!(bytes.len(), 0)
     }
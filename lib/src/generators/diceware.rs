@@ -0,0 +1,219 @@
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type InputResult<'a> = Result<PhysicalInput, BoxedError<'a>>;
+type IndexResult<'a> = Result<Vec<usize>, BoxedError<'a>>;
+
+const RANKS: &str = "A23456789TJQK";
+const SUITS: &str = "CDHS";
+
+/// Randomness gathered by hand, such as dice rolls, coin flips or a shuffled deck of cards.
+///
+/// Each observation is stored as a symbol together with the number of equally likely outcomes it was drawn from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhysicalInput {
+    symbols: Vec<(u32, u32)>
+}
+
+impl PhysicalInput {
+    /// Parses a sequence of six-sided dice rolls, such as `41524 11263`.
+    pub fn dice<'a>(input: &str) -> InputResult<'a> {
+        let symbols = input.chars()
+            .filter(|character| !character.is_whitespace())
+            .map(|character| match character {
+                '1'..='6' => Ok((character as u32 - '1' as u32, 6)),
+                _ => Err(format!("{character} is not the roll of a six-sided die").into())
+            })
+            .collect::<Result<Vec<_>, BoxedError>>()?;
+
+        PhysicalInput::new(symbols)
+    }
+
+    /// Parses a sequence of coin flips written as `H` for heads and `T` for tails.
+    pub fn coins<'a>(input: &str) -> InputResult<'a> {
+        let symbols = input.chars()
+            .filter(|character| !character.is_whitespace())
+            .map(|character| match character.to_ascii_uppercase() {
+                'H' => Ok((0, 2)),
+                'T' => Ok((1, 2)),
+                _ => Err(format!("{character} is not a coin flip, which must be H or T").into())
+            })
+            .collect::<Result<Vec<_>, BoxedError>>()?;
+
+        PhysicalInput::new(symbols)
+    }
+
+    /// Parses the order of a shuffled deck of cards, such as `AS 7H TD QC`.
+    ///
+    /// Each card is written as its rank (`A`, `2` to `10` or `T`, `J`, `Q` or `K`) followed by its suit (`C`, `D`, `H` or `S`).
+    /// Any number of cards may be given, but none may appear twice.
+    pub fn cards<'a>(input: &str) -> InputResult<'a> {
+        let mut remaining = (0..52).collect::<Vec<u32>>();
+        let mut symbols = Vec::new();
+
+        for card in input.split_whitespace() {
+            let normalized = card.to_ascii_uppercase().replace("10", "T");
+            let mut characters = normalized.chars();
+            let (rank, suit) = match (characters.next(), characters.next(), characters.next()) {
+                (Some(rank), Some(suit), None) => (RANKS.find(rank), SUITS.find(suit)),
+                _ => (None, None)
+            };
+
+            let value = match (rank, suit) {
+                (Some(rank), Some(suit)) => (suit * RANKS.len() + rank) as u32,
+                _ => return Err(format!("{card} is not a playing card").into())
+            };
+
+            // A card is one of the cards which haven't been drawn yet, so its position among them is the outcome.
+            match remaining.iter().position(|&candidate| candidate == value) {
+                Some(position) => {
+                    symbols.push((position as u32, remaining.len() as u32));
+                    remaining.remove(position);
+                },
+                None => return Err(format!("The card {card} appears more than once").into())
+            }
+        }
+
+        PhysicalInput::new(symbols)
+    }
+
+    fn new<'a>(symbols: Vec<(u32, u32)>) -> InputResult<'a> {
+        if symbols.is_empty() { return Err("No physical randomness was provided".into()); }
+
+        Ok(PhysicalInput { symbols })
+    }
+
+    /// The entropy provided by the input, in bits.
+    pub fn entropy(&self) -> f64 {
+        self.symbols.iter()
+            .map(|&(_, outcomes)| (outcomes as f64).log2())
+            .sum()
+    }
+
+    /// Selects [count] indices uniformly from `0..range` without introducing any bias.
+    ///
+    /// The observations are accumulated into a single uniform value, from which an index is taken whenever its range
+    /// is large enough. Values that would favour some indices over others are not discarded; the unused remainder of
+    /// the range is kept as a smaller uniform value, so very little of the input is wasted. Five dice map directly
+    /// onto an index in a list of 7776 words.
+    pub fn select<'a>(&self, count: usize, range: usize) -> IndexResult<'a> {
+        let range = range as u128;
+        let mut indices = Vec::with_capacity(count);
+        let mut value: u128 = 0;
+        let mut bound: u128 = 1;
+
+        for &(symbol, outcomes) in &self.symbols {
+            if indices.len() == count { break; }
+
+            value = value * outcomes as u128 + symbol as u128;
+            bound *= outcomes as u128;
+
+            if bound < range { continue; }
+
+            let limit = bound - bound % range;
+
+            if value < limit {
+                indices.push((value % range) as usize);
+                value /= range;
+                bound = limit / range;
+            }
+            else {
+                value -= limit;
+                bound -= limit;
+            }
+        }
+
+        if indices.len() < count {
+            return Err(format!(
+                "The input provides {:.2} bits of entropy, which was only enough to choose {} of {count} words without bias",
+                self.entropy(),
+                indices.len()
+            ).into());
+        }
+
+        Ok(indices)
+    }
+}
+
+/// Formats an index into a list of [range] items as the dice rolls which select it, such as `41524`.
+///
+/// Dice only map directly onto a list whose length is a power of six, such as 7776 words for five dice. Other lists
+/// are chosen from by rejection with a carried remainder, so no rolls correspond to an index and `None` is returned.
+pub fn dice_index(index: usize, range: usize) -> Option<String> {
+    let mut digits = 1;
+
+    while 6_usize.pow(digits) < range { digits += 1; }
+
+    if 6_usize.pow(digits) != range { return None; }
+
+    Some((0..digits)
+        .rev()
+        .map(|digit| char::from(b'1' + (index / 6_usize.pow(digit) % 6) as u8))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::wordlist::load_canonical_wordlist;
+
+    use super::*;
+
+    #[test]
+    fn maps_dice_onto_canonical_wordlist() {
+        let wordlist = load_canonical_wordlist().unwrap();
+        let input = PhysicalInput::dice("11111 66666 11121").unwrap();
+        let words = input.select(3, wordlist.len())
+            .unwrap()
+            .into_iter()
+            .map(|index| wordlist[index].as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(words, ["abacus", "zoom", "ablaze"]);
+    }
+
+    #[test]
+    fn formats_dice_index() {
+        assert_eq!(dice_index(0, 7776).unwrap(), "11111");
+        assert_eq!(dice_index(7775, 7776).unwrap(), "66666");
+        assert_eq!(dice_index(6, 7776).unwrap(), "11121");
+        assert_eq!(dice_index(6, 1296).unwrap(), "1121");
+        assert_eq!(dice_index(6, 2048), None);
+    }
+
+    #[test]
+    fn extracts_uniform_indices_from_coins() {
+        let mut counts = [0; 3];
+
+        // Every sequence of eight flips is equally likely, so each index should be selected equally often.
+        for flips in 0..256 {
+            let input = PhysicalInput::coins(&format!("{flips:08b}").replace('0', "H").replace('1', "T")).unwrap();
+
+            if let Ok(indices) = input.select(1, 3) {
+                counts[indices[0]] += 1;
+            }
+        }
+
+        assert_eq!(counts[0], counts[1]);
+        assert_eq!(counts[1], counts[2]);
+    }
+
+    #[test]
+    fn calculates_entropy_of_deck() {
+        let deck = SUITS.chars()
+            .flat_map(|suit| RANKS.chars().map(move |rank| format!("{rank}{suit}")))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let input = PhysicalInput::cards(&deck).unwrap();
+        let expected = (1..=52).map(|value| (value as f64).log2()).sum::<f64>();
+
+        assert!((input.entropy() - expected).abs() < 1e-9);
+        assert_eq!(input.select(17, 7776).unwrap().len(), 17);
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert!(PhysicalInput::dice("12347").is_err());
+        assert!(PhysicalInput::coins("HTX").is_err());
+        assert!(PhysicalInput::cards("AS 10H AS").is_err());
+        assert!(PhysicalInput::cards("1S").is_err());
+        assert!(PhysicalInput::dice("1234").unwrap().select(1, 7776).is_err());
+    }
+}
@@ -1,12 +1,11 @@
-use rand::{distributions::Uniform, thread_rng, Rng};
+use rand::{distributions::Uniform, Rng};
 
-pub fn generate_digits(length: usize) -> Vec<u8> {
+pub fn generate_digits<R: Rng + Sized>(length: usize, rng: &mut R) -> Vec<u8> {
     if length == 0 { return Vec::<u8>::new(); }
 
     let distribution = Uniform::new_inclusive(0, 9);
 
-    thread_rng()
-        .sample_iter(distribution)
+    rng.sample_iter(distribution)
         .take(length)
         .map(|value| char::from_digit(value, 10).unwrap())
         .collect::<String>()
@@ -17,11 +16,13 @@ pub fn generate_digits(length: usize) -> Vec<u8> {
 mod tests {
     use std::str::from_utf8;
 
+    use rand::thread_rng;
+
     use super::*;
 
     #[test]
     fn generates_ten_thousand_digits() {
-        let bytes = generate_digits(10000);
+        let bytes = generate_digits(10000, &mut thread_rng());
         let string = from_utf8(&bytes).unwrap();
 
         assert_eq!(string.chars().count(), 10000)
@@ -30,7 +31,7 @@ mod tests {
 
     #[test]
     fn generates_zero_digits() {
-        let bytes = generate_digits(0);
+        let bytes = generate_digits(0, &mut thread_rng());
 
         assert_eq!(bytes.len(), 0)
     }
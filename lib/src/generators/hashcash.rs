@@ -0,0 +1,132 @@
+use std::str::from_utf8;
+
+use rand::Rng;
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use sha1::{Digest, Sha1};
+
+use crate::generators::generate_base64;
+use crate::time::{format_hashcash_date, parse_hashcash_date};
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type UnitResult<'a> = Result<(), BoxedError<'a>>;
+
+/// The number of random bytes in the random part of a stamp, which are encoded as 16 characters of Base64.
+const HASHCASH_RANDOM_LENGTH: usize = 12;
+
+/// The number of seconds a stamp may be dated in the future, to allow for clocks which disagree.
+const HASHCASH_CLOCK_SKEW: u64 = 600;
+
+/// The most bits a stamp can claim, since a SHA-1 digest is 160 bits long.
+pub const MAX_HASHCASH_BITS: u32 = 160;
+
+/// What a stamp must satisfy to be accepted.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HashcashRequirements<'a> {
+    /// The fewest leading zero bits the stamp may claim.
+    pub bits: u32,
+    /// The resource the stamp must have been minted for, if any.
+    pub resource: Option<&'a str>,
+    /// The number of seconds since the Unix epoch at which the stamp is checked.
+    pub now: u64,
+    /// The most seconds which may have passed since the stamp was minted.
+    pub max_age: u64
+}
+
+fn leading_zero_bits(stamp: &[u8]) -> u32 {
+    let digest = Sha1::digest(stamp);
+    let zero_bytes = digest.iter().take_while(|&&byte| byte == 0).count();
+
+    match digest.get(zero_bytes) {
+        Some(byte) => zero_bytes as u32 * 8 + byte.leading_zeros(),
+        None => MAX_HASHCASH_BITS
+    }
+}
+
+/// Mint a Hashcash version 1 stamp for a resource, whose SHA-1 digest begins with [bits] zero bits.
+///
+/// The stamp is dated with the given number of seconds since the Unix epoch. Counters are tried in parallel, but
+/// the smallest one which works is always chosen, so the stamp only depends on the random number generator. Each
+/// additional bit doubles the expected amount of work.
+pub fn mint_hashcash<R: Rng + Sized>(resource: &str, bits: u32, timestamp: u64, rng: &mut R) -> Vec<u8> {
+    let random = generate_base64(false, HASHCASH_RANDOM_LENGTH, rng);
+    let prefix = format!("1:{bits}:{}:{resource}::{}:", format_hashcash_date(timestamp), from_utf8(&random).unwrap());
+
+    let counter = (0..usize::MAX)
+        .into_par_iter()
+        .by_exponential_blocks()
+        .find_first(|counter| leading_zero_bits(format!("{prefix}{counter}").as_bytes()) >= bits)
+        .unwrap();
+
+    format!("{prefix}{counter}").into_bytes()
+}
+
+/// Check that a stamp is well formed, claims and has enough bits, was minted for the resource and is recent.
+///
+/// A stamp can only be trusted once, so a service should also remember the stamps it has accepted until they expire.
+pub fn verify_hashcash<'a>(stamp: &str, requirements: &HashcashRequirements) -> UnitResult<'a> {
+    let fields = stamp.trim().split(':').collect::<Vec<_>>();
+
+    let [version, bits, date, resource, _, random, counter] = fields[..] else {
+        return Err("A stamp must have seven fields separated by colons".into());
+    };
+
+    let is_base64 = |field: &str| !field.is_empty() && field.bytes().all(|byte| byte.is_ascii_alphanumeric() || b"+/=".contains(&byte));
+
+    if version != "1" { return Err(format!("Only version 1 stamps are supported, but the stamp is version {version}").into()); }
+    if !is_base64(random) || !is_base64(counter) { return Err("The random part and counter of a stamp must be Base64".into()); }
+
+    let bits = bits.parse::<u32>().map_err(|_| "The stamp doesn't claim a number of bits")?;
+    let minted = parse_hashcash_date(date).ok_or("The date of the stamp must be YYMMDD, YYMMDDhhmm or YYMMDDhhmmss")?;
+
+    if bits < requirements.bits {
+        return Err(format!("The stamp claims {bits} bits, but {} are required", requirements.bits).into());
+    }
+    if leading_zero_bits(stamp.trim().as_bytes()) < bits {
+        return Err(format!("The stamp claims {bits} bits, but its digest doesn't begin with that many zero bits").into());
+    }
+    if let Some(expected) = requirements.resource {
+        if resource != expected { return Err(format!("The stamp was minted for {resource}, not {expected}").into()); }
+    }
+    if minted > requirements.now + HASHCASH_CLOCK_SKEW { return Err("The stamp is dated in the future".into()); }
+    if requirements.now.saturating_sub(minted) > requirements.max_age { return Err("The stamp has expired".into()); }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use super::*;
+
+    const STAMP: &str = "1:20:1303030600:adam@cypherspace.org::McMybZIhxKXu57jd:ckvi";
+
+    fn requirements(now: u64) -> HashcashRequirements<'static> {
+        HashcashRequirements { bits: 20, resource: Some("adam@cypherspace.org"), now, max_age: 86400 }
+    }
+
+    #[test]
+    fn verifies_known_stamp() {
+        let minted = 1_362_290_400;
+
+        assert!(verify_hashcash(STAMP, &requirements(minted + 3600)).is_ok());
+        assert!(verify_hashcash(STAMP, &HashcashRequirements { bits: 21, ..requirements(minted) }).is_err());
+        assert!(verify_hashcash(STAMP, &HashcashRequirements { resource: Some("eve@example.com"), ..requirements(minted) }).is_err());
+        assert!(verify_hashcash(STAMP, &requirements(minted + 86401)).is_err());
+        assert!(verify_hashcash(STAMP, &requirements(minted - 3600)).is_err());
+        assert!(verify_hashcash(&STAMP.replace("ckvi", "ckvj"), &requirements(minted)).is_err());
+        assert!(verify_hashcash(&STAMP.replace(":20:", ":24:"), &HashcashRequirements { bits: 0, ..requirements(minted) }).is_err());
+        assert!(verify_hashcash("1:20:1303030600:adam@cypherspace.org:McMybZIhxKXu57jd:ckvi", &requirements(minted)).is_err());
+    }
+
+    #[test]
+    fn mints_verifiable_stamps() {
+        let now = 1_700_000_000;
+        let stamp = String::from_utf8(mint_hashcash("signup@example.com", 12, now, &mut thread_rng())).unwrap();
+        let requirements = HashcashRequirements { bits: 12, resource: Some("signup@example.com"), now, max_age: 60 };
+
+        assert!(stamp.starts_with("1:12:231114221320:signup@example.com::"));
+        assert!(leading_zero_bits(stamp.as_bytes()) >= 12);
+        assert!(verify_hashcash(&stamp, &requirements).is_ok());
+    }
+}
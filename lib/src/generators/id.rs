@@ -0,0 +1,173 @@
+use rand::Rng;
+use ulid::Ulid;
+use uuid::{Builder, Uuid};
+
+use crate::time::unix_timestamp_millis;
+
+/// The width of the counter embedded in the random part of a version 7 UUID.
+const UUID_V7_COUNTER_BITS: u32 = 42;
+
+/// The width of the random part of a version 7 UUID which follows the counter.
+const UUID_V7_RANDOM_BITS: u32 = 32;
+
+/// The width of the counter embedded in the random part of a ULID.
+const ULID_COUNTER_BITS: u32 = 40;
+
+/// The width of the random part of a ULID which follows the counter.
+const ULID_RANDOM_BITS: u32 = 40;
+
+/// The shared state of a batch of time-ordered identifiers.
+///
+/// Every identifier in a batch is stamped with the same clock reading and a counter derived from its index,
+/// so the batch sorts in index order no matter which thread produced which identifier.
+/// The counter starts at a random value with its most significant bit cleared to leave room for the batch,
+/// and carries into the timestamp in the unlikely event that it overflows.
+pub struct IdSequence {
+    timestamp: u64,
+    origin: u64,
+    width: u32
+}
+
+impl IdSequence {
+    fn new<R: Rng + Sized>(width: u32, rng: &mut R) -> Self {
+        let timestamp = unix_timestamp_millis();
+        let origin = rng.gen_range(0..1 << (width - 1));
+
+        IdSequence { timestamp, origin, width }
+    }
+
+    /// Starts a batch of version 7 UUIDs.
+    pub fn uuid_v7<R: Rng + Sized>(rng: &mut R) -> Self {
+        IdSequence::new(UUID_V7_COUNTER_BITS, rng)
+    }
+
+    /// Starts a batch of ULIDs.
+    pub fn ulid<R: Rng + Sized>(rng: &mut R) -> Self {
+        IdSequence::new(ULID_COUNTER_BITS, rng)
+    }
+
+    /// Gets the timestamp and counter of the identifier at [index] in the batch.
+    fn get(&self, index: usize) -> (u64, u64) {
+        let counter = self.origin + index as u64;
+        let mask = (1 << self.width) - 1;
+
+        (self.timestamp + (counter >> self.width), counter & mask)
+    }
+}
+
+/// Generate a random version 4 UUID.
+pub fn generate_uuid_v4<R: Rng + Sized>(rng: &mut R) -> Vec<u8> {
+    let bytes = rng.gen::<[u8; 16]>();
+
+    Builder::from_random_bytes(bytes)
+        .into_uuid()
+        .to_string()
+        .into_bytes()
+}
+
+/// Generate the version 7 UUID at [index] in a batch.
+///
+/// The 74 bits following the timestamp are a 42-bit counter followed by 32 random bits,
+/// which corresponds to the fixed-length dedicated counter method of RFC 9562.
+pub fn generate_uuid_v7<R: Rng + Sized>(sequence: &IdSequence, index: usize, rng: &mut R) -> Vec<u8> {
+    let (timestamp, counter) = sequence.get(index);
+    let random = rng.gen::<u32>() as u128;
+    let bits = ((counter as u128) << UUID_V7_RANDOM_BITS) | random;
+
+    // Interleave the timestamp, version, random data and variant according to the layout of RFC 9562.
+    let value = ((timestamp as u128 & 0xFFFF_FFFF_FFFF) << 80)
+        | (0x7 << 76)
+        | ((bits >> 62) << 64)
+        | (0x2 << 62)
+        | (bits & 0x3FFF_FFFF_FFFF_FFFF);
+
+    Uuid::from_u128(value)
+        .to_string()
+        .into_bytes()
+}
+
+/// Generate the ULID at [index] in a batch.
+///
+/// The 80 bits following the timestamp are a 40-bit counter followed by 40 random bits.
+pub fn generate_ulid<R: Rng + Sized>(sequence: &IdSequence, index: usize, rng: &mut R) -> Vec<u8> {
+    let (timestamp, counter) = sequence.get(index);
+    let random = rng.gen_range(0..1_u128 << ULID_RANDOM_BITS);
+    let bits = ((counter as u128) << ULID_RANDOM_BITS) | random;
+
+    Ulid::from_parts(timestamp, bits)
+        .to_string()
+        .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::from_utf8;
+
+    use rand::thread_rng;
+    use uuid::{Variant, Version};
+
+    use super::*;
+
+    fn parse_uuid(bytes: &[u8]) -> Uuid {
+        Uuid::parse_str(from_utf8(bytes).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn generates_uuid_v4() {
+        let uuid = parse_uuid(&generate_uuid_v4(&mut thread_rng()));
+
+        assert_eq!(uuid.get_version(), Some(Version::Random));
+        assert_eq!(uuid.get_variant(), Variant::RFC4122);
+    }
+
+    #[test]
+    fn generates_uuid_v7() {
+        let sequence = IdSequence::uuid_v7(&mut thread_rng());
+        let uuid = parse_uuid(&generate_uuid_v7(&sequence, 0, &mut thread_rng()));
+        let (seconds, _) = uuid.get_timestamp().unwrap().to_unix();
+
+        assert_eq!(uuid.get_version(), Some(Version::SortRand));
+        assert_eq!(uuid.get_variant(), Variant::RFC4122);
+        assert_eq!(seconds, sequence.timestamp / 1000);
+    }
+
+    #[test]
+    fn generates_monotonic_uuid_v7_batch() {
+        let sequence = IdSequence::uuid_v7(&mut thread_rng());
+        let batch = (0..1000)
+            .rev()
+            .map(|index| generate_uuid_v7(&sequence, index, &mut thread_rng()))
+            .collect::<Vec<_>>();
+
+        assert!(batch.windows(2).all(|pair| pair[0] > pair[1]));
+    }
+
+    #[test]
+    fn generates_ulid() {
+        let sequence = IdSequence::ulid(&mut thread_rng());
+        let bytes = generate_ulid(&sequence, 0, &mut thread_rng());
+        let ulid = Ulid::from_string(from_utf8(&bytes).unwrap()).unwrap();
+
+        assert_eq!(bytes.len(), 26);
+        assert_eq!(ulid.timestamp_ms(), sequence.timestamp);
+    }
+
+    #[test]
+    fn generates_monotonic_ulid_batch() {
+        let sequence = IdSequence::ulid(&mut thread_rng());
+        let batch = (0..1000)
+            .rev()
+            .map(|index| generate_ulid(&sequence, index, &mut thread_rng()))
+            .collect::<Vec<_>>();
+
+        assert!(batch.windows(2).all(|pair| pair[0] > pair[1]));
+    }
+
+    #[test]
+    fn carries_counter_into_timestamp() {
+        let sequence = IdSequence { timestamp: 1000, origin: (1 << ULID_COUNTER_BITS) - 1, width: ULID_COUNTER_BITS };
+
+        assert_eq!(sequence.get(0), (1000, (1 << ULID_COUNTER_BITS) - 1));
+        assert_eq!(sequence.get(1), (1001, 0));
+    }
+}
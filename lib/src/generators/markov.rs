@@ -2,6 +2,25 @@ use rand::Rng;
 
 use crate::markov::{Generator, MarkovGenerator};
 
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type EstimateResult<'a> = Result<MarkovEstimate, BoxedError<'a>>;
+type RangeResult<'a> = Result<(usize, usize, MarkovEstimate), BoxedError<'a>>;
+
+/// The number of words sampled when estimating the entropy of a generator.
+const ESTIMATE_SAMPLES: usize = 500;
+
+/// The number of words which may be discarded for each word sampled before a length range is deemed unreachable.
+const ESTIMATE_ATTEMPTS: usize = 1000;
+
+/// An estimate of the entropy of the words produced by a Markov generator.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MarkovEstimate {
+    /// The entropy of a word, in bits.
+    pub entropy: f64,
+    /// The entropy of each letter in a word, in bits.
+    pub rate: f64
+}
+
 trait Capitalize {
     fn capitalize(&mut self);
 }
@@ -25,16 +44,72 @@ pub fn generate_markov<R: Rng + Sized>(generator: &Generator, capitalize: bool,
 
     if capitalize { username.capitalize(); }
 
-    return username.into_bytes()
+    username.into_bytes()
+}
+
+/// Estimate the entropy of words generated within a length range.
+///
+/// The self-information of a sample of words is averaged and then corrected for the words discarded for falling
+/// outside the length range, which gives a Monte Carlo estimate of the Shannon entropy of the output.
+pub fn estimate_markov_entropy<'a, R: Rng + Sized>(generator: &Generator, minimum: usize, maximum: usize, rng: &mut R) -> EstimateResult<'a> {
+    let mut attempts = 0;
+    let mut accepted = 0;
+    let mut information = 0.0;
+    let mut letters = 0;
+
+    while accepted < ESTIMATE_SAMPLES {
+        if attempts > ESTIMATE_ATTEMPTS * (accepted + 1) {
+            return Err(format!("The model rarely produces words of at least {minimum} and fewer than {maximum} letters").into());
+        }
+
+        attempts += 1;
+
+        let word = match generator.generate_within(maximum, rng) {
+            Some(word) if word.len() >= minimum => word.replace("#", ""),
+            _ => continue
+        };
+
+        accepted += 1;
+        information += generator.information(&word);
+        letters += word.len();
+    }
+
+    let acceptance = accepted as f64 / attempts as f64;
+
+    Ok(MarkovEstimate {
+        entropy: information / accepted as f64 + acceptance.log2(),
+        rate: information / letters as f64
+    })
+}
+
+/// Find the shortest length range, as wide as the one given, whose words are estimated to carry at least [bits] of entropy.
+pub fn markov_range_for_bits<'a, R: Rng + Sized>(generator: &Generator, bits: usize, minimum: usize, maximum: usize, rng: &mut R) -> RangeResult<'a> {
+    let width = maximum.saturating_sub(minimum).max(1);
+    let estimate = estimate_markov_entropy(generator, minimum, maximum, rng)?;
+
+    if estimate.entropy >= bits as f64 { return Ok((minimum, maximum, estimate)); }
+
+    // Start from the length at which the average letter would provide enough entropy, and lengthen the words from there.
+    let mut minimum = minimum.max((bits as f64 / estimate.rate).ceil() as usize);
+
+    loop {
+        let maximum = maximum.max(minimum + width);
+        let estimate = estimate_markov_entropy(generator, minimum, maximum, rng)?;
+
+        if estimate.entropy >= bits as f64 { return Ok((minimum, maximum, estimate)); }
+
+        minimum += 1;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::str::from_utf8;
 
-    use rand::thread_rng;
+    use rand::{thread_rng, SeedableRng};
 
     use crate::corpus::load_default_corpus;
+    use crate::random::Csprng;
 
     use super::*;
 
@@ -55,4 +130,19 @@ mod tests {
 
         assert!(!username.is_empty());
     }
+
+    #[test]
+    fn estimates_entropy() {
+        let corpus = load_default_corpus().unwrap();
+        let generator = Generator::new(&corpus, 3, 0.0, false);
+
+        // The estimate is a random sample, so a fixed seed keeps the test from failing by chance.
+        let mut rng = Csprng::seed_from_u64(0);
+        let short = estimate_markov_entropy(&generator, 2, 6, &mut rng).unwrap();
+        let (minimum, maximum, long) = markov_range_for_bits(&generator, short.entropy.ceil() as usize + 4, 2, 6, &mut rng).unwrap();
+
+        assert!(short.entropy > 0.0 && short.rate > 0.0);
+        assert!(long.entropy > short.entropy);
+        assert_eq!(maximum - minimum, 4);
+    }
 }
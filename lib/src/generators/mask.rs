@@ -0,0 +1,257 @@
+use clap::ValueEnum;
+use num_bigint::BigUint;
+use num_traits::One;
+use rand::{seq::SliceRandom, Rng};
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type MaskResult<'a> = Result<Mask, BoxedError<'a>>;
+type CharsetResult<'a> = Result<Vec<char>, BoxedError<'a>>;
+
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const DIGITS: &str = "0123456789";
+const HEX_LOWER: &str = "0123456789abcdef";
+const HEX_UPPER: &str = "0123456789ABCDEF";
+const SPECIAL: &str = " !\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+const VOWELS: &str = "aeiou";
+const CONSONANTS: &str = "bcdfghjklmnpqrstvwxyz";
+
+/// The syntax used to describe a mask.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum MaskSyntax {
+    /// Hashcat-style placeholders, such as `?u?l?l?l-?d?d?d?d`
+    Hashcat,
+    /// Single-letter placeholders, such as `Cvcvc##!`
+    Template
+}
+
+impl MaskSyntax {
+    /// Guesses the syntax of a mask, which is assumed to use Hashcat placeholders if it contains a `?`.
+    pub fn detect(mask: &str) -> Self {
+        if mask.contains('?') { MaskSyntax::Hashcat } else { MaskSyntax::Template }
+    }
+}
+
+/// A sequence of positions, each of which is drawn from its own character set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mask {
+    positions: Vec<Vec<char>>
+}
+
+impl Mask {
+    /// Parses a mask, resolving the Hashcat placeholders `?1` to `?4` to the [custom] character sets.
+    pub fn parse<'a>(mask: &str, syntax: MaskSyntax, custom: &[Option<String>; 4]) -> MaskResult<'a> {
+        let positions = match syntax {
+            MaskSyntax::Hashcat => parse_hashcat(mask, custom)?,
+            MaskSyntax::Template => parse_template(mask)?
+        };
+
+        if positions.is_empty() { return Err("The mask is empty".into()); }
+
+        let positions = positions.into_iter()
+            .map(|mut charset| {
+                charset.sort();
+                charset.dedup();
+                charset
+            })
+            .collect::<Vec<_>>();
+
+        if positions.iter().any(|charset| charset.is_empty()) {
+            return Err("The mask contains an empty character set".into());
+        }
+
+        Ok(Mask { positions })
+    }
+
+    /// The number of distinct strings described by the mask.
+    pub fn keyspace(&self) -> BigUint {
+        self.positions.iter()
+            .fold(BigUint::one(), |keyspace, charset| keyspace * charset.len())
+    }
+
+    /// The entropy of a string drawn from the mask, in bits.
+    pub fn entropy(&self) -> f64 {
+        self.positions.iter()
+            .map(|charset| (charset.len() as f64).log2())
+            .sum()
+    }
+}
+
+/// Gets the character set of a built-in Hashcat placeholder.
+fn hashcat_charset(placeholder: char) -> Option<String> {
+    let charset = match placeholder {
+        'l' => LOWERCASE.to_string(),
+        'u' => UPPERCASE.to_string(),
+        'd' => DIGITS.to_string(),
+        'h' => HEX_LOWER.to_string(),
+        'H' => HEX_UPPER.to_string(),
+        's' => SPECIAL.to_string(),
+        'a' => [LOWERCASE, UPPERCASE, DIGITS, SPECIAL].concat(),
+        _ => return None
+    };
+
+    Some(charset)
+}
+
+/// Expands a custom character set, which may itself contain built-in placeholders.
+fn expand_custom<'a>(definition: &str) -> CharsetResult<'a> {
+    let mut charset = Vec::new();
+    let mut characters = definition.chars();
+
+    while let Some(character) = characters.next() {
+        if character != '?' {
+            charset.push(character);
+            continue;
+        }
+
+        match characters.next() {
+            Some('?') => charset.push('?'),
+            Some(placeholder) => match hashcat_charset(placeholder) {
+                Some(value) => charset.extend(value.chars()),
+                None => return Err(format!("Unknown placeholder ?{placeholder} in custom character set").into())
+            },
+            None => return Err("A custom character set ends with an incomplete placeholder".into())
+        }
+    }
+
+    Ok(charset)
+}
+
+fn parse_hashcat<'a>(mask: &str, custom: &[Option<String>; 4]) -> Result<Vec<Vec<char>>, BoxedError<'a>> {
+    let mut positions = Vec::new();
+    let mut characters = mask.chars();
+
+    while let Some(character) = characters.next() {
+        if character != '?' {
+            positions.push(vec![character]);
+            continue;
+        }
+
+        let charset = match characters.next() {
+            Some('?') => vec!['?'],
+            Some(placeholder @ '1'..='4') => {
+                let index = placeholder as usize - '1' as usize;
+
+                match &custom[index] {
+                    Some(definition) => expand_custom(definition)?,
+                    None => return Err(format!("The mask uses ?{placeholder}, but no custom character set {placeholder} was given").into())
+                }
+            },
+            Some(placeholder) => match hashcat_charset(placeholder) {
+                Some(value) => value.chars().collect(),
+                None => return Err(format!("Unknown placeholder ?{placeholder} in mask").into())
+            },
+            None => return Err("The mask ends with an incomplete placeholder".into())
+        };
+
+        positions.push(charset);
+    }
+
+    Ok(positions)
+}
+
+fn parse_template<'a>(mask: &str) -> Result<Vec<Vec<char>>, BoxedError<'a>> {
+    let mut positions = Vec::new();
+    let mut characters = mask.chars();
+
+    while let Some(character) = characters.next() {
+        let charset: Vec<char> = match character {
+            'C' => CONSONANTS.to_uppercase().chars().collect(),
+            'c' => CONSONANTS.chars().collect(),
+            'V' => VOWELS.to_uppercase().chars().collect(),
+            'v' => VOWELS.chars().collect(),
+            'L' => UPPERCASE.chars().collect(),
+            'l' => LOWERCASE.chars().collect(),
+            '#' => DIGITS.chars().collect(),
+            '!' => SPECIAL.chars().collect(),
+            '*' => [LOWERCASE, UPPERCASE, DIGITS, SPECIAL].concat().chars().collect(),
+            '\\' => match characters.next() {
+                Some(literal) => vec![literal],
+                None => return Err("The mask ends with an incomplete escape sequence".into())
+            },
+            literal => vec![literal]
+        };
+
+        positions.push(charset);
+    }
+
+    Ok(positions)
+}
+
+/// Generate a random string shaped by a mask.
+pub fn generate_mask<R: Rng + Sized>(mask: &Mask, rng: &mut R) -> Vec<u8> {
+    mask.positions.iter()
+        .map(|charset| charset.choose(rng).unwrap())
+        .collect::<String>()
+        .into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+    use regex::Regex;
+
+    use super::*;
+
+    const NO_CUSTOM: [Option<String>; 4] = [None, None, None, None];
+
+    fn generate(mask: &str, custom: &[Option<String>; 4]) -> String {
+        let mask = Mask::parse(mask, MaskSyntax::detect(mask), custom).unwrap();
+
+        String::from_utf8(generate_mask(&mask, &mut thread_rng())).unwrap()
+    }
+
+    #[test]
+    fn generates_hashcat_mask() {
+        let string = generate("?u?l?l?l-?d?d?d?d-?s", &NO_CUSTOM);
+        let regex = Regex::new(r"^[A-Z][a-z]{3}-\d{4}-[ -/:-@\[-`{-~]$").unwrap();
+
+        assert!(regex.is_match(&string), "{string}");
+    }
+
+    #[test]
+    fn generates_template_mask() {
+        let string = generate("Cvcvc##!", &NO_CUSTOM);
+        let regex = Regex::new(r"^[B-DF-HJ-NP-TV-Z][aeiou][b-df-hj-np-tv-z][aeiou][b-df-hj-np-tv-z]\d{2}[ -/:-@\[-`{-~]$").unwrap();
+
+        assert!(regex.is_match(&string), "{string}");
+    }
+
+    #[test]
+    fn generates_custom_charsets() {
+        let custom = [Some("abc".to_string()), Some("?dX".to_string()), None, None];
+        let string = generate("?1?2??", &custom);
+        let regex = Regex::new(r"^[abc][0-9X]\?$").unwrap();
+
+        assert!(regex.is_match(&string), "{string}");
+    }
+
+    #[test]
+    fn escapes_template_placeholders() {
+        assert_eq!(generate(r"\C\#", &NO_CUSTOM), "C#");
+    }
+
+    #[test]
+    fn calculates_keyspace() {
+        let mask = Mask::parse("?d?d?d?d", MaskSyntax::Hashcat, &NO_CUSTOM).unwrap();
+
+        assert_eq!(mask.keyspace(), BigUint::from(10000_u32));
+        assert!((mask.entropy() - 10000_f64.log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn deduplicates_custom_charsets() {
+        let custom = [Some("?l?l".to_string()), None, None, None];
+        let mask = Mask::parse("?1", MaskSyntax::Hashcat, &custom).unwrap();
+
+        assert_eq!(mask.keyspace(), BigUint::from(26_u32));
+    }
+
+    #[test]
+    fn rejects_invalid_masks() {
+        assert!(Mask::parse("?x", MaskSyntax::Hashcat, &NO_CUSTOM).is_err());
+        assert!(Mask::parse("?d?", MaskSyntax::Hashcat, &NO_CUSTOM).is_err());
+        assert!(Mask::parse("?1", MaskSyntax::Hashcat, &NO_CUSTOM).is_err());
+        assert!(Mask::parse("", MaskSyntax::Template, &NO_CUSTOM).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use pbkdf2::pbkdf2_hmac;
+use rand::Rng;
+use sha2::{Digest, Sha256, Sha512};
+use unicode_normalization::UnicodeNormalization;
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type StringResult<'a> = Result<String, BoxedError<'a>>;
+
+/// The number of bits encoded by each word of a mnemonic.
+const BITS_PER_WORD: usize = 11;
+
+/// The number of PBKDF2 iterations used to derive a seed from a mnemonic.
+const SEED_ITERATIONS: u32 = 2048;
+
+/// The number of words a BIP39 mnemonic may contain.
+pub const MNEMONIC_LENGTHS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// Gets the bit at [index] of a big-endian sequence of bytes.
+fn get_bit(bytes: &[u8], index: usize) -> bool {
+    bytes[index / 8] & (0x80 >> (index % 8)) != 0
+}
+
+/// Encodes entropy as a mnemonic, appending a checksum taken from the first bits of its SHA-256 digest.
+fn encode_mnemonic(wordlist: &[String], entropy: &[u8]) -> String {
+    let checksum = Sha256::digest(entropy)[0];
+    let bytes = [entropy, &[checksum]].concat();
+    let words = (entropy.len() * 8 + entropy.len() / 4) / BITS_PER_WORD;
+
+    (0..words)
+        .map(|word| {
+            let index = (0..BITS_PER_WORD)
+                .fold(0, |index, bit| (index << 1) | get_bit(&bytes, word * BITS_PER_WORD + bit) as usize);
+
+            wordlist[index].as_str()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Finds a word in the wordlist, which may be abbreviated to any unambiguous prefix of at least four letters.
+fn find_word(wordlist: &[String], word: &str) -> Option<usize> {
+    if let Some(index) = wordlist.iter().position(|value| value == word) { return Some(index); }
+    if word.chars().count() < 4 { return None; }
+
+    let mut matches = wordlist.iter()
+        .enumerate()
+        .filter(|(_, value)| value.starts_with(word));
+
+    match (matches.next(), matches.next()) {
+        (Some((index, _)), None) => Some(index),
+        _ => None
+    }
+}
+
+/// Generate a BIP39 mnemonic with the given number of words.
+pub fn generate_mnemonic<R: Rng + Sized>(wordlist: &[String], words: usize, rng: &mut R) -> Vec<u8> {
+    assert!(MNEMONIC_LENGTHS.contains(&words), "A mnemonic must contain 12, 15, 18, 21 or 24 words");
+
+    let mut entropy = vec![0; words * BITS_PER_WORD * 32 / 33 / 8];
+
+    rng.fill_bytes(&mut entropy);
+
+    encode_mnemonic(wordlist, &entropy).into_bytes()
+}
+
+/// Validates a mnemonic, returning it in its canonical form.
+///
+/// Words are matched case-insensitively and may be abbreviated to their first four letters.
+pub fn validate_mnemonic<'a>(wordlist: &[String], mnemonic: &str) -> StringResult<'a> {
+    let normalized = mnemonic.nfkd().collect::<String>().to_lowercase();
+    let words = normalized.split_whitespace().collect::<Vec<_>>();
+
+    if !MNEMONIC_LENGTHS.contains(&words.len()) {
+        return Err(format!("A mnemonic must contain 12, 15, 18, 21 or 24 words, but this one contains {}", words.len()).into());
+    }
+
+    let mut indices = Vec::with_capacity(words.len());
+    let mut bytes = vec![0_u8; (words.len() * BITS_PER_WORD).div_ceil(8)];
+
+    for (position, word) in words.iter().enumerate() {
+        let index = match find_word(wordlist, word) {
+            Some(index) => index,
+            None => return Err(format!("Word {} of the mnemonic ({word}) is not in the BIP39 wordlist", position + 1).into())
+        };
+
+        for bit in 0..BITS_PER_WORD {
+            if index & (1 << (BITS_PER_WORD - 1 - bit)) != 0 {
+                let offset = position * BITS_PER_WORD + bit;
+
+                bytes[offset / 8] |= 0x80 >> (offset % 8);
+            }
+        }
+
+        indices.push(index);
+    }
+
+    // The entropy is always a whole number of bytes, and is followed by one checksum bit for every 32 bits of entropy.
+    let length = words.len() * BITS_PER_WORD * 32 / 33 / 8;
+    let shift = 8 - length / 4;
+
+    if bytes[length] >> shift != Sha256::digest(&bytes[..length])[0] >> shift {
+        return Err("The checksum of the mnemonic is invalid".into());
+    }
+
+    let canonical = indices.iter()
+        .map(|&index| wordlist[index].as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Ok(canonical)
+}
+
+/// Derive the 64-byte BIP39 seed of a mnemonic, which is protected by an optional passphrase.
+pub fn derive_mnemonic_seed(mnemonic: &str, passphrase: &str) -> Vec<u8> {
+    let password = mnemonic.nfkd().collect::<String>();
+    let salt = format!("mnemonic{passphrase}").nfkd().collect::<String>();
+    let mut seed = vec![0; 64];
+
+    pbkdf2_hmac::<Sha512>(password.as_bytes(), salt.as_bytes(), SEED_ITERATIONS, &mut seed);
+
+    seed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::from_utf8;
+
+    use rand::thread_rng;
+
+    use crate::wordlist::load_bip39_wordlist;
+
+    use super::*;
+
+    // Test vectors from the reference implementation, all of which use the passphrase TREZOR.
+    const VECTORS: [(&str, &str, &str); 3] = [
+        (
+            "00000000000000000000000000000000",
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        ),
+        (
+            "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607"
+        ),
+        (
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+            "dd48c104698c30cfe2b6142103248622fb7bb0ff692eebb00089b32d22484e1613912f0a5b694407be899ffd31ed3992c456cdf60f5d4564b8ba3f05a69890ad"
+        )
+    ];
+
+    #[test]
+    fn encodes_test_vectors() {
+        let wordlist = load_bip39_wordlist().unwrap();
+
+        for (entropy, mnemonic, _) in VECTORS {
+            assert_eq!(encode_mnemonic(&wordlist, &hex::decode(entropy).unwrap()), mnemonic);
+        }
+    }
+
+    #[test]
+    fn derives_test_vector_seeds() {
+        for (_, mnemonic, seed) in VECTORS {
+            assert_eq!(hex::encode(derive_mnemonic_seed(mnemonic, "TREZOR")), seed);
+        }
+    }
+
+    #[test]
+    fn generates_valid_mnemonics() {
+        let wordlist = load_bip39_wordlist().unwrap();
+
+        for words in MNEMONIC_LENGTHS {
+            let bytes = generate_mnemonic(&wordlist, words, &mut thread_rng());
+            let mnemonic = from_utf8(&bytes).unwrap();
+
+            assert_eq!(mnemonic.split(' ').count(), words);
+            assert_eq!(validate_mnemonic(&wordlist, mnemonic).unwrap(), mnemonic);
+        }
+    }
+
+    #[test]
+    fn normalizes_abbreviated_mnemonics() {
+        let wordlist = load_bip39_wordlist().unwrap();
+        let mnemonic = "LEGA winn  thank year wave sausage worth useful legal winner thank yell";
+
+        assert_eq!(validate_mnemonic(&wordlist, mnemonic).unwrap(), VECTORS[1].1);
+    }
+
+    #[test]
+    fn rejects_invalid_mnemonics() {
+        let wordlist = load_bip39_wordlist().unwrap();
+
+        // A bad checksum, an unknown word and a missing word.
+        assert!(validate_mnemonic(&wordlist, &"abandon ".repeat(12)).is_err());
+        assert!(validate_mnemonic(&wordlist, &VECTORS[0].1.replace("about", "aboot")).is_err());
+        assert!(validate_mnemonic(&wordlist, VECTORS[0].1.trim_end_matches(" about")).is_err());
+    }
+}
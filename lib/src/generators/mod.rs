@@ -1,15 +1,41 @@
 pub mod binary;
 pub mod password;
+pub mod policy;
 pub mod passphrase;
 pub mod username;
 pub mod digits;
 pub mod number;
 pub mod markov;
+pub mod id;
+pub mod mask;
+pub mod pattern;
+pub mod mnemonic;
+pub mod diceware;
+pub mod otp;
+pub mod ssh;
+pub mod x25519;
+pub mod cert;
+pub mod hashcash;
+pub mod prime;
+pub mod token;
 
 pub use binary::*;
 pub use password::*;
+pub use policy::*;
 pub use passphrase::*;
 pub use username::*;
 pub use digits::*;
 pub use number::*;
 pub use markov::*;
+pub use id::*;
+pub use mask::*;
+pub use pattern::*;
+pub use mnemonic::*;
+pub use diceware::*;
+pub use otp::*;
+pub use ssh::*;
+pub use x25519::*;
+pub use cert::*;
+pub use hashcash::*;
+pub use prime::*;
+pub use token::*;
@@ -1,9 +1,9 @@
 use std::process::exit;
 
 use log::error;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
-pub fn generate_number(minimum: usize, maximum: usize) -> Vec<u8> {
+pub fn generate_number<R: Rng + Sized>(minimum: usize, maximum: usize, rng: &mut R) -> Vec<u8> {
     if minimum >= maximum {
         error!("{} is greater than or equal to {}", minimum, maximum);
         exit(-1);
@@ -11,8 +11,7 @@ pub fn generate_number(minimum: usize, maximum: usize) -> Vec<u8> {
 
     let range = minimum..=maximum;
 
-    thread_rng()
-        .gen_range(range)
+    rng.gen_range(range)
         .to_string()
         .into_bytes()
 }
@@ -21,13 +20,15 @@ pub fn generate_number(minimum: usize, maximum: usize) -> Vec<u8> {
 mod tests {
     use std::str::from_utf8;
 
+    use rand::thread_rng;
+
     use super::*;
 
     #[test]
     fn generates_number() {
-        let bytes = generate_number(0, 1024);
+        let bytes = generate_number(0, 1024, &mut thread_rng());
         let string = from_utf8(&bytes).unwrap();
-        let number = u64::from_str_radix(string, 10).unwrap();
+        let number = string.parse::<u64>().unwrap();
 
         assert!(number < 1024)
     }
@@ -0,0 +1,203 @@
+use clap::ValueEnum;
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+
+use crate::generators::generate_bytes;
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type SecretResult<'a> = Result<Vec<u8>, BoxedError<'a>>;
+
+/// The number of bytes in a secret when no length is given, which is the 160 bits recommended by RFC 4226.
+pub const DEFAULT_OTP_SECRET_LENGTH: usize = 20;
+
+/// The hash function used to compute the HMAC of a one-time password.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum OtpAlgorithm {
+    #[default]
+    Sha1,
+    Sha256,
+    Sha512
+}
+
+/// The parameters shared by an authenticator and the service which verifies its codes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OtpParameters {
+    pub algorithm: OtpAlgorithm,
+    pub digits: u32,
+    pub period: u64
+}
+
+impl OtpAlgorithm {
+    /// The name of the algorithm as it appears in an otpauth URI.
+    pub fn name(&self) -> &'static str {
+        match self {
+            OtpAlgorithm::Sha1 => "SHA1",
+            OtpAlgorithm::Sha256 => "SHA256",
+            OtpAlgorithm::Sha512 => "SHA512"
+        }
+    }
+
+    fn hmac(&self, key: &[u8], message: &[u8]) -> Vec<u8> {
+        // HMAC accepts keys of any length, so creating one can't fail.
+        match self {
+            OtpAlgorithm::Sha1 => {
+                let mut mac = Hmac::<Sha1>::new_from_slice(key).unwrap();
+
+                mac.update(message);
+                mac.finalize().into_bytes().to_vec()
+            },
+            OtpAlgorithm::Sha256 => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+
+                mac.update(message);
+                mac.finalize().into_bytes().to_vec()
+            },
+            OtpAlgorithm::Sha512 => {
+                let mut mac = Hmac::<Sha512>::new_from_slice(key).unwrap();
+
+                mac.update(message);
+                mac.finalize().into_bytes().to_vec()
+            }
+        }
+    }
+}
+
+impl Default for OtpParameters {
+    fn default() -> Self {
+        OtpParameters { algorithm: OtpAlgorithm::Sha1, digits: 6, period: 30 }
+    }
+}
+
+/// Percent-encodes everything except the unreserved characters of RFC 3986.
+fn percent_encode(value: &str) -> String {
+    value.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => char::from(byte).to_string(),
+            _ => format!("%{byte:02X}")
+        })
+        .collect()
+}
+
+/// Generate a random secret of [length] bytes, encoded as unpadded Base32 as authenticator apps expect.
+pub fn generate_otp_secret<R: Rng + Sized>(length: usize, rng: &mut R) -> Vec<u8> {
+    BASE32_NOPAD.encode(&generate_bytes(length, rng)).into_bytes()
+}
+
+/// Writes a Base32 secret the way authenticators expect it, in uppercase without whitespace, hyphens or padding.
+pub fn normalize_otp_secret(secret: &str) -> String {
+    secret.chars()
+        .filter(|character| !character.is_whitespace() && *character != '-' && *character != '=')
+        .collect::<String>()
+        .to_ascii_uppercase()
+}
+
+/// Decodes a Base32 secret, ignoring case, whitespace, hyphens and padding.
+pub fn decode_otp_secret<'a>(secret: &str) -> SecretResult<'a> {
+    match BASE32_NOPAD.decode(normalize_otp_secret(secret).as_bytes()) {
+        Ok(bytes) if !bytes.is_empty() => Ok(bytes),
+        Ok(_) => Err("The secret is empty".into()),
+        Err(error) => Err(format!("The secret is not valid Base32: {error}").into())
+    }
+}
+
+/// Build an `otpauth://totp/` URI which can be shown as a QR code to provision an authenticator.
+pub fn create_otp_uri(secret: &str, account: &str, issuer: Option<&str>, parameters: &OtpParameters) -> String {
+    let label = match issuer {
+        Some(issuer) => format!("{}:{}", percent_encode(issuer), percent_encode(account)),
+        None => percent_encode(account)
+    };
+    let issuer = match issuer {
+        Some(issuer) => format!("&issuer={}", percent_encode(issuer)),
+        None => String::new()
+    };
+
+    format!(
+        "otpauth://totp/{label}?secret={secret}{issuer}&algorithm={}&digits={}&period={}",
+        parameters.algorithm.name(),
+        parameters.digits,
+        parameters.period
+    )
+}
+
+/// Compute the HOTP code of RFC 4226 for the given counter.
+pub fn compute_hotp(secret: &[u8], counter: u64, parameters: &OtpParameters) -> String {
+    let digest = parameters.algorithm.hmac(secret, &counter.to_be_bytes());
+
+    // Dynamic truncation takes 31 bits from the offset given by the low nibble of the last byte.
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let value = u32::from_be_bytes([digest[offset], digest[offset + 1], digest[offset + 2], digest[offset + 3]]) & 0x7fff_ffff;
+    let code = value as u64 % 10_u64.pow(parameters.digits);
+
+    format!("{code:0width$}", width = parameters.digits as usize)
+}
+
+/// Compute the TOTP code of RFC 6238 at the given number of seconds since the Unix epoch.
+pub fn compute_totp(secret: &[u8], timestamp: u64, parameters: &OtpParameters) -> String {
+    compute_hotp(secret, timestamp / parameters.period, parameters)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::from_utf8;
+
+    use rand::thread_rng;
+
+    use super::*;
+
+    const SHA1_SECRET: &[u8] = b"12345678901234567890";
+    const SHA256_SECRET: &[u8] = b"12345678901234567890123456789012";
+    const SHA512_SECRET: &[u8] = b"1234567890123456789012345678901234567890123456789012345678901234";
+
+    #[test]
+    fn computes_rfc4226_codes() {
+        let expected = ["755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583", "399871", "520489"];
+
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(compute_hotp(SHA1_SECRET, counter as u64, &OtpParameters::default()), *code);
+        }
+    }
+
+    #[test]
+    fn computes_rfc6238_codes() {
+        let vectors = [
+            (59, "94287082", "46119246", "90693936"),
+            (1111111109, "07081804", "68084774", "25091201"),
+            (1234567890, "89005924", "91819424", "93441116"),
+            (20000000000, "65353130", "77737706", "47863826")
+        ];
+
+        for (timestamp, sha1, sha256, sha512) in vectors {
+            for (algorithm, secret, code) in [
+                (OtpAlgorithm::Sha1, SHA1_SECRET, sha1),
+                (OtpAlgorithm::Sha256, SHA256_SECRET, sha256),
+                (OtpAlgorithm::Sha512, SHA512_SECRET, sha512)
+            ] {
+                let parameters = OtpParameters { algorithm, digits: 8, period: 30 };
+
+                assert_eq!(compute_totp(secret, timestamp, &parameters), code);
+            }
+        }
+    }
+
+    #[test]
+    fn generates_decodable_secrets() {
+        let secret = generate_otp_secret(DEFAULT_OTP_SECRET_LENGTH, &mut thread_rng());
+        let string = from_utf8(&secret).unwrap();
+
+        assert_eq!(string.len(), 32);
+        assert_eq!(decode_otp_secret(&string.to_lowercase()).unwrap().len(), DEFAULT_OTP_SECRET_LENGTH);
+        assert_eq!(decode_otp_secret("GEZD GNBV GY3T QOJQ GEZD GNBV GY3T QOJQ").unwrap(), SHA1_SECRET);
+        assert!(decode_otp_secret("GEZD1").is_err());
+        assert_eq!(normalize_otp_secret("gezd-gnbv gy3t\tqojq===="), "GEZDGNBVGY3TQOJQ");
+    }
+
+    #[test]
+    fn creates_uri() {
+        let uri = create_otp_uri("GEZDGNBV", "build bot@example.com", Some("ACME Co"), &OtpParameters::default());
+
+        assert_eq!(uri, "otpauth://totp/ACME%20Co:build%20bot%40example.com?secret=GEZDGNBV&issuer=ACME%20Co&algorithm=SHA1&digits=6&period=30");
+    }
+}
@@ -1,13 +1,63 @@
-use rand::{distributions::Slice, thread_rng, Rng};
+use clap::ValueEnum;
+use rand::{distributions::Slice, seq::{index::sample, SliceRandom}, Rng};
+
+/// The symbols which may be inserted into a passphrase.
+const SYMBOLS: &str = "!#$%&*?@";
+
+/// The digits which may be inserted into a passphrase.
+const DIGITS: &str = "0123456789";
+
+/// The letters which may be replaced in leetspeak, and their replacements.
+const LEET: [(char, char); 6] = [('a', '4'), ('e', '3'), ('i', '1'), ('o', '0'), ('s', '5'), ('t', '7')];
+
+/// How the words of a passphrase are capitalized.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Capitalization {
+    /// Leave every word in lowercase
+    #[default]
+    Lower,
+    /// Write every word in uppercase
+    Upper,
+    /// Capitalize the first letter of every word
+    Title,
+    /// Capitalize the first letter of each word with a probability of one half
+    Random
+}
+
+/// The transformations applied to a passphrase to satisfy password rules.
+///
+/// Every random choice is uniform, and the words are assumed not to contain digits or symbols,
+/// which keeps each transformation reversible so that its entropy is added in full.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PassphraseStyle {
+    /// How the words are capitalized.
+    pub capitalization: Capitalization,
+    /// Insert a random digit at a random word boundary.
+    pub digit: bool,
+    /// Insert a random symbol at a random word boundary.
+    pub symbol: bool,
+    /// Separate each pair of words with a character drawn from this set, instead of a fixed separator.
+    pub separators: Vec<char>,
+    /// Replace each letter which has a leetspeak equivalent with a probability of one half.
+    pub leet: bool,
+    /// Never use the same word twice.
+    pub unique: bool
+}
+
+impl PassphraseStyle {
+    /// Whether the style leaves the words and separators exactly as they were chosen.
+    pub fn is_plain(&self) -> bool {
+        *self == PassphraseStyle::default()
+    }
+}
 
 /// Generate a passphrase.
-pub fn generate_passphrase(wordlist: &[String], separator: &str, length: usize) -> Vec<u8> {
+pub fn generate_passphrase<R: Rng + Sized>(wordlist: &[String], separator: &str, length: usize, rng: &mut R) -> Vec<u8> {
     if length == 0 { return Vec::<u8>::new(); }
 
     let distribution = Slice::new(wordlist).unwrap();
 
-    thread_rng()
-        .sample_iter(distribution)
+    rng.sample_iter(distribution)
         .take(length)
         .map(|value| value.to_owned())
         .collect::<Vec<String>>()
@@ -15,15 +65,148 @@ pub fn generate_passphrase(wordlist: &[String], separator: &str, length: usize)
         .into_bytes()
 }
 
+/// Gets the casings a word may be written in.
+fn casings(word: &str, capitalization: Capitalization) -> Vec<String> {
+    let title = || {
+        let mut characters = word.chars();
+
+        characters.next()
+            .map(|first| first.to_uppercase().chain(characters).collect())
+            .unwrap_or_default()
+    };
+
+    let mut casings = match capitalization {
+        Capitalization::Lower => vec![word.to_owned()],
+        Capitalization::Upper => vec![word.to_uppercase()],
+        Capitalization::Title => vec![title()],
+        Capitalization::Random => vec![word.to_owned(), title()]
+    };
+
+    casings.dedup();
+
+    casings
+}
+
+/// Gets the leetspeak replacement of a letter in either case.
+fn leet_replacement(character: char) -> Option<char> {
+    LEET.iter()
+        .find(|(letter, _)| *letter == character.to_ascii_lowercase())
+        .map(|&(_, replacement)| replacement)
+}
+
+/// Counts the letters of a word which may be replaced in leetspeak.
+fn count_leet(word: &str) -> usize {
+    word.chars()
+        .filter(|&character| leet_replacement(character).is_some())
+        .count()
+}
+
+/// Calculates the entropy added to a word by capitalization and leetspeak, in bits.
+fn transform_bits(word: &str, style: &PassphraseStyle) -> f64 {
+    let casings = (casings(word, style.capitalization).len() as f64).log2();
+
+    if !style.leet { return casings; }
+
+    // A first letter which is replaced reads the same in every casing, which hides the casing half of the time.
+    let hidden = match word.chars().next().and_then(leet_replacement) {
+        Some(_) if casings > 0.0 => casings / 2.0,
+        _ => 0.0
+    };
+
+    casings + count_leet(word) as f64 - hidden
+}
+
+/// Calculate the entropy of a passphrase with [length] words from [wordlist] in the given style, in bits.
+pub fn passphrase_entropy(wordlist: &[String], length: usize, style: &PassphraseStyle) -> f64 {
+    if length == 0 { return 0.0; }
+
+    let choices = wordlist.len() as f64;
+    let words = match style.unique {
+        true => (0..length).map(|index| (choices - index as f64).log2()).sum::<f64>(),
+        false => length as f64 * choices.log2()
+    };
+
+    // Each position holds a uniformly chosen word, so the transformations add their average over the wordlist.
+    let transforms = length as f64 * wordlist.iter()
+        .map(|word| transform_bits(word, style))
+        .sum::<f64>() / choices;
+
+    let boundaries = ((length + 1) as f64).log2();
+    let digit = if style.digit { (DIGITS.len() as f64).log2() + boundaries } else { 0.0 };
+    let symbol = if style.symbol { (SYMBOLS.len() as f64).log2() + boundaries } else { 0.0 };
+
+    let mut separators = style.separators.clone();
+
+    separators.sort();
+    separators.dedup();
+
+    let separators = match separators.len() {
+        0 => 0.0,
+        count => (length - 1) as f64 * (count as f64).log2()
+    };
+
+    words + transforms + digit + symbol + separators
+}
+
+/// Generate a passphrase, transforming it according to a style.
+pub fn generate_styled_passphrase<R: Rng + Sized>(wordlist: &[String], separator: &str, length: usize, style: &PassphraseStyle, rng: &mut R) -> Vec<u8> {
+    if style.is_plain() { return generate_passphrase(wordlist, separator, length, rng); }
+    if length == 0 { return Vec::<u8>::new(); }
+
+    let words = match style.unique {
+        true => sample(rng, wordlist.len(), length).into_iter().map(|index| wordlist[index].as_str()).collect::<Vec<_>>(),
+        false => (0..length).map(|_| wordlist.choose(rng).unwrap().as_str()).collect()
+    };
+
+    let words = words.into_iter()
+        .map(|word| {
+            let casing = casings(word, style.capitalization).choose(rng).unwrap().clone();
+
+            if !style.leet { return casing; }
+
+            casing.chars()
+                .map(|character| match leet_replacement(character) {
+                    Some(replacement) if rng.gen_bool(0.5) => replacement,
+                    _ => character
+                })
+                .collect()
+        })
+        .collect::<Vec<String>>();
+
+    // Characters inserted at a boundary follow the word before it, so the boundary before the first word is the start.
+    let mut insertions = vec![String::new(); length + 1];
+
+    if style.digit { insertions[rng.gen_range(0..=length)].push(*DIGITS.as_bytes().choose(rng).unwrap() as char); }
+    if style.symbol { insertions[rng.gen_range(0..=length)].push(*SYMBOLS.as_bytes().choose(rng).unwrap() as char); }
+
+    let mut output = insertions[0].clone();
+
+    for (index, word) in words.iter().enumerate() {
+        if index > 0 {
+            match style.separators.choose(rng) {
+                Some(character) => output.push(*character),
+                None => output.push_str(separator)
+            }
+        }
+
+        output.push_str(word);
+        output.push_str(&insertions[index + 1]);
+    }
+
+    output.into_bytes()
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::from_utf8;
 
+    use rand::thread_rng;
+    use regex::Regex;
     use zstd::bulk::decompress;
 
     use super::*;
 
-    fn word_count(buffer: &Vec<u8>) -> usize {
+    fn word_count(buffer: &[u8]) -> usize {
         from_utf8(buffer)
             .unwrap()
             .split(' ')
@@ -45,7 +228,7 @@ mod tests {
     #[test]
     fn generates_ten_thousand_word_passphrase() {
         let wordlist = load_test_wordlist();
-        let bytes = generate_passphrase(&wordlist, &" ".to_string(), 10000);
+        let bytes = generate_passphrase(&wordlist, " ", 10000, &mut thread_rng());
         let count = word_count(&bytes);
 
         assert_eq!(count, 10000)
@@ -54,7 +237,7 @@ mod tests {
     #[test]
     fn generates_hundred_thousand_word_passphrase() {
         let wordlist = load_test_wordlist();
-        let bytes = generate_passphrase(&wordlist, &" ".to_string(), 100000);
+        let bytes = generate_passphrase(&wordlist, " ", 100000, &mut thread_rng());
         let count = word_count(&bytes);
 
         assert_eq!(count, 100000)
@@ -63,7 +246,7 @@ mod tests {
     #[test]
     fn generates_empty_passphrase() {
         let wordlist = load_test_wordlist();
-        let bytes = generate_passphrase(&wordlist, &" ".to_string(), 0);
+        let bytes = generate_passphrase(&wordlist, " ", 0, &mut thread_rng());
 
         assert_eq!(bytes.len(), 0)
     }
@@ -73,6 +256,82 @@ mod tests {
     fn empty_wordlist_panics() {
         let wordlist = Vec::<String>::new();
 
-        generate_passphrase(&wordlist, &" ".to_string(), 1);
+        generate_passphrase(&wordlist, " ", 1, &mut thread_rng());
+    }
+
+    #[test]
+    fn applies_transformations() {
+        let wordlist = vec!["sat".to_string(), "mop".to_string()];
+        let style = PassphraseStyle {
+            capitalization: Capitalization::Title,
+            digit: true,
+            symbol: true,
+            separators: vec!['-', '+'],
+            leet: true,
+            unique: true
+        };
+        let bytes = generate_styled_passphrase(&wordlist, " ", 2, &style, &mut thread_rng());
+        let passphrase = from_utf8(&bytes).unwrap();
+        let regex = Regex::new(r"^[0-9!#$%&*?@]*([S5][a4][t7]|M[o0]p)[0-9!#$%&*?@]*[-+][0-9!#$%&*?@]*([S5][a4][t7]|M[o0]p)[0-9!#$%&*?@]*$").unwrap();
+
+        assert!(regex.is_match(passphrase), "{passphrase}");
+    }
+
+    #[test]
+    fn calculates_transformation_entropy() {
+        let wordlist = vec!["sat".to_string(), "mop".to_string()];
+        let style = PassphraseStyle { capitalization: Capitalization::Random, leet: true, ..Default::default() };
+
+        // The word and its capitalization take a bit each, the words have 3 and 1 leetable letters, and replacing the
+        // first letter of "sat" hides its capitalization half of the time.
+        assert!((passphrase_entropy(&wordlist, 1, &style) - (1.0 + 1.0 + (3.0 - 0.5 + 1.0) / 2.0)).abs() < 1e-9);
+
+        let style = PassphraseStyle { digit: true, separators: vec!['-', '+'], unique: true, ..Default::default() };
+
+        assert!((passphrase_entropy(&wordlist, 2, &style) - (1.0 + 10_f64.log2() + 3_f64.log2() + 1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn applies_leetspeak_in_every_capitalization() {
+        let wordlist = vec!["toast".to_string()];
+        let patterns = [
+            (Capitalization::Lower, r"^[t7][o0][a4][s5][t7]$"),
+            (Capitalization::Upper, r"^[T7][O0][A4][S5][T7]$"),
+            (Capitalization::Title, r"^[T7][o0][a4][s5][t7]$"),
+            (Capitalization::Random, r"^[tT7][o0][a4][s5][t7]$")
+        ];
+
+        for (capitalization, pattern) in patterns {
+            let style = PassphraseStyle { capitalization, leet: true, ..Default::default() };
+            let regex = Regex::new(pattern).unwrap();
+            let mut replaced = [false; 5];
+
+            for _ in 0..200 {
+                let bytes = generate_styled_passphrase(&wordlist, " ", 1, &style, &mut thread_rng());
+                let passphrase = from_utf8(&bytes).unwrap();
+
+                assert!(regex.is_match(passphrase), "{passphrase}");
+
+                for (index, character) in passphrase.chars().enumerate() {
+                    replaced[index] |= character.is_ascii_digit();
+                }
+            }
+
+            assert_eq!(replaced, [true; 5], "{capitalization:?}");
+            assert!((passphrase_entropy(&wordlist, 1, &style) - (5.0 + if capitalization == Capitalization::Random { 0.5 } else { 0.0 })).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn never_repeats_unique_words() {
+        let wordlist = load_test_wordlist();
+        let style = PassphraseStyle { unique: true, ..Default::default() };
+        let bytes = generate_styled_passphrase(&wordlist[..100], " ", 100, &style, &mut thread_rng());
+        let mut words = from_utf8(&bytes).unwrap().split(' ').collect::<Vec<_>>();
+
+        words.sort();
+        words.dedup();
+
+        assert_eq!(words.len(), 100);
     }
 }
@@ -1,12 +1,11 @@
-use rand::{thread_rng, Rng, distributions::Slice};
+use rand::{Rng, distributions::Slice};
 
-pub fn generate_password(character_set: &[char], length: usize) -> Vec<u8> {
+pub fn generate_password<R: Rng + Sized>(character_set: &[char], length: usize, rng: &mut R) -> Vec<u8> {
     if length == 0 { return Vec::<u8>::new(); }
 
     let distribution = Slice::new(character_set).unwrap();
 
-    thread_rng()
-        .sample_iter(distribution)
+    rng.sample_iter(distribution)
         .take(length)
         .collect::<String>()
         .into_bytes()
@@ -16,12 +15,14 @@ pub fn generate_password(character_set: &[char], length: usize) -> Vec<u8> {
 mod tests {
     use std::str::from_utf8;
 
+    use rand::thread_rng;
+
     use super::*;
 
     #[test]
     fn generates_ten_thousand_character_password() {
         let character_set: Vec<char> = "!@*-_.0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ".chars().collect();
-        let bytes = generate_password(&character_set, 10000);
+        let bytes = generate_password(&character_set, 10000, &mut thread_rng());
         let string = from_utf8(&bytes).unwrap();
 
         assert_eq!(string.chars().count(), 10000)
@@ -30,7 +31,7 @@ mod tests {
     #[test]
     fn generates_empty_password() {
         let character_set: Vec<char> = "!@*-_.0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ".chars().collect();
-        let bytes = generate_password(&character_set, 0);
+        let bytes = generate_password(&character_set, 0, &mut thread_rng());
 
         assert_eq!(bytes.len(), 0)
     }
@@ -0,0 +1,554 @@
+use std::collections::{HashMap, VecDeque};
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+use rand::Rng;
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type PatternResult<'a> = Result<Pattern, BoxedError<'a>>;
+type NodeResult<'a> = Result<Node, BoxedError<'a>>;
+
+/// A set of printable ASCII characters, where bit `n` stands for the character with the code `n`.
+type CharacterSet = u128;
+
+/// The first printable ASCII character.
+const FIRST: u8 = b' ';
+
+/// The last printable ASCII character.
+const LAST: u8 = b'~';
+
+/// Every printable ASCII character.
+const PRINTABLE: CharacterSet = ((1 << (LAST as u32 + 1)) - 1) & !((1 << FIRST as u32) - 1);
+
+/// The largest number of states an automaton may grow to before a pattern is considered too complex.
+const STATE_LIMIT: usize = 200_000;
+
+/// A node in the syntax tree of a regular expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Set(CharacterSet),
+    Concatenation(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repetition(Box<Node>, usize, usize)
+}
+
+fn range(first: u8, last: u8) -> CharacterSet {
+    (first..=last).fold(0, |set, character| set | 1 << character)
+}
+
+fn digits() -> CharacterSet {
+    range(b'0', b'9')
+}
+
+fn words() -> CharacterSet {
+    range(b'a', b'z') | range(b'A', b'Z') | digits() | 1 << b'_'
+}
+
+fn spaces() -> CharacterSet {
+    1 << b' '
+}
+
+/// A recursive descent parser for a bounded subset of the regular expression syntax.
+struct Parser<'a> {
+    characters: Vec<char>,
+    position: usize,
+    source: &'a str
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.characters.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let character = self.peek();
+
+        self.position += 1;
+
+        character
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += 1;
+
+            true
+        }
+        else { false }
+    }
+
+    fn error<'b>(&self, message: &str) -> BoxedError<'b> {
+        format!("{message} at offset {} in {:?}", self.position, self.source).into()
+    }
+
+    fn parse_alternation<'b>(&mut self) -> NodeResult<'b> {
+        let mut branches = vec![self.parse_concatenation()?];
+
+        while self.eat('|') {
+            branches.push(self.parse_concatenation()?);
+        }
+
+        Ok(if branches.len() == 1 { branches.remove(0) } else { Node::Alternation(branches) })
+    }
+
+    fn parse_concatenation<'b>(&mut self) -> NodeResult<'b> {
+        let mut nodes = Vec::new();
+
+        while let Some(character) = self.peek() {
+            if character == '|' || character == ')' { break; }
+
+            nodes.push(self.parse_repetition()?);
+        }
+
+        Ok(Node::Concatenation(nodes))
+    }
+
+    fn parse_repetition<'b>(&mut self) -> NodeResult<'b> {
+        let mut node = self.parse_atom()?;
+
+        loop {
+            let (minimum, maximum) = match self.peek() {
+                Some('?') => { self.position += 1; (0, 1) },
+                Some('{') => { self.position += 1; self.parse_bounds()? },
+                Some('*') | Some('+') => return Err(self.error("Unbounded repetition is not supported")),
+                _ => break
+            };
+
+            // A lazy quantifier matches the same language as a greedy one.
+            self.eat('?');
+
+            if self.peek() == Some('+') { return Err(self.error("Possessive quantifiers are not supported")); }
+
+            node = Node::Repetition(Box::new(node), minimum, maximum);
+        }
+
+        Ok(node)
+    }
+
+    fn parse_number(&mut self) -> Option<usize> {
+        let start = self.position;
+
+        while self.peek().is_some_and(|character| character.is_ascii_digit()) { self.position += 1; }
+
+        self.characters[start..self.position].iter().collect::<String>().parse().ok()
+    }
+
+    fn parse_bounds<'b>(&mut self) -> Result<(usize, usize), BoxedError<'b>> {
+        let minimum = self.parse_number();
+        let maximum = if self.eat(',') { self.parse_number() } else { minimum };
+
+        if !self.eat('}') { return Err(self.error("Expected '}' to close the repetition")); }
+
+        match (minimum, maximum) {
+            (_, None) => Err(self.error("Unbounded repetition is not supported")),
+            (minimum, Some(maximum)) if minimum.unwrap_or(0) > maximum => Err(self.error("The repetition has a minimum greater than its maximum")),
+            (minimum, Some(maximum)) => Ok((minimum.unwrap_or(0), maximum))
+        }
+    }
+
+    fn parse_atom<'b>(&mut self) -> NodeResult<'b> {
+        let character = self.next().ok_or_else(|| self.error("Unexpected end of pattern"))?;
+
+        match character {
+            '(' => {
+                if self.eat('?') && !self.eat(':') { return Err(self.error("Only non-capturing groups are supported")); }
+
+                let node = self.parse_alternation()?;
+
+                if !self.eat(')') { return Err(self.error("Expected ')' to close the group")); }
+
+                Ok(node)
+            },
+            '[' => self.parse_class().map(Node::Set),
+            '.' => Ok(Node::Set(PRINTABLE)),
+            '\\' => self.parse_escape().map(Node::Set),
+            '^' | '$' => Err(self.error("Anchors are only supported at the ends of the pattern")),
+            '?' | '*' | '+' | '{' => Err(self.error("Nothing to repeat")),
+            ')' => Err(self.error("Unbalanced ')'")),
+            literal => self.literal(literal).map(Node::Set)
+        }
+    }
+
+    fn literal<'b>(&self, character: char) -> Result<CharacterSet, BoxedError<'b>> {
+        if !(FIRST as char..=LAST as char).contains(&character) {
+            return Err(self.error(&format!("Only printable ASCII characters are supported, but found {character:?}")));
+        }
+
+        Ok(1 << character as u32)
+    }
+
+    fn parse_escape<'b>(&mut self) -> Result<CharacterSet, BoxedError<'b>> {
+        let character = self.next().ok_or_else(|| self.error("The pattern ends with an incomplete escape sequence"))?;
+
+        match character {
+            'd' => Ok(digits()),
+            'D' => Ok(PRINTABLE & !digits()),
+            'w' => Ok(words()),
+            'W' => Ok(PRINTABLE & !words()),
+            's' => Ok(spaces()),
+            'S' => Ok(PRINTABLE & !spaces()),
+            literal if literal.is_ascii_punctuation() || literal == ' ' => self.literal(literal),
+            other => Err(self.error(&format!("Unsupported escape sequence \\{other}")))
+        }
+    }
+
+    fn parse_class<'b>(&mut self) -> Result<CharacterSet, BoxedError<'b>> {
+        let negated = self.eat('^');
+        let mut set: CharacterSet = 0;
+        let mut first = true;
+
+        loop {
+            let character = self.next().ok_or_else(|| self.error("Expected ']' to close the character class"))?;
+
+            if character == ']' && !first { break; }
+
+            first = false;
+
+            let start = match character {
+                '\\' => {
+                    let escaped = self.parse_escape()?;
+
+                    // Only a single escaped character can start a range.
+                    if escaped.count_ones() != 1 { set |= escaped; continue; }
+
+                    escaped.trailing_zeros() as u8
+                },
+                literal => self.literal(literal)?.trailing_zeros() as u8
+            };
+
+            if self.peek() == Some('-') && self.characters.get(self.position + 1).is_some_and(|next| *next != ']') {
+                self.position += 1;
+
+                let end = match self.next() {
+                    Some('\\') => {
+                        let escaped = self.parse_escape()?;
+
+                        if escaped.count_ones() != 1 { return Err(self.error("Invalid range in character class")); }
+
+                        escaped.trailing_zeros() as u8
+                    },
+                    Some(literal) => self.literal(literal)?.trailing_zeros() as u8,
+                    None => return Err(self.error("Expected ']' to close the character class"))
+                };
+
+                if start > end { return Err(self.error("Invalid range in character class")); }
+
+                set |= range(start, end);
+            }
+            else {
+                set |= 1 << start;
+            }
+        }
+
+        Ok(if negated { PRINTABLE & !set } else { set })
+    }
+}
+
+/// A state of a nondeterministic finite automaton.
+#[derive(Default)]
+struct NfaState {
+    epsilon: Vec<usize>,
+    transition: Option<(CharacterSet, usize)>
+}
+
+/// A nondeterministic finite automaton built from a syntax tree by Thompson's construction.
+#[derive(Default)]
+struct Nfa {
+    states: Vec<NfaState>
+}
+
+impl Nfa {
+    fn add(&mut self) -> Result<usize, BoxedError<'static>> {
+        if self.states.len() >= STATE_LIMIT { return Err("The pattern is too complex".into()); }
+
+        self.states.push(NfaState::default());
+
+        Ok(self.states.len() - 1)
+    }
+
+    /// Builds a fragment of the automaton, returning its start and end states.
+    fn build(&mut self, node: &Node) -> Result<(usize, usize), BoxedError<'static>> {
+        let start = self.add()?;
+        let mut end = start;
+
+        match node {
+            Node::Set(set) => {
+                end = self.add()?;
+                self.states[start].transition = Some((*set, end));
+            },
+            Node::Concatenation(nodes) => {
+                for node in nodes {
+                    let (first, last) = self.build(node)?;
+
+                    self.states[end].epsilon.push(first);
+                    end = last;
+                }
+            },
+            Node::Alternation(branches) => {
+                end = self.add()?;
+
+                for branch in branches {
+                    let (first, last) = self.build(branch)?;
+
+                    self.states[start].epsilon.push(first);
+                    self.states[last].epsilon.push(end);
+                }
+            },
+            Node::Repetition(node, minimum, maximum) => {
+                for index in 0..*maximum {
+                    let (first, last) = self.build(node)?;
+
+                    self.states[end].epsilon.push(first);
+
+                    // Every repetition beyond the minimum may be skipped.
+                    if index >= *minimum { self.states[end].epsilon.push(last); }
+
+                    end = last;
+                }
+            }
+        }
+
+        Ok((start, end))
+    }
+
+    fn closure(&self, states: &mut Vec<usize>) {
+        let mut stack = states.clone();
+
+        while let Some(state) = stack.pop() {
+            for next in self.states[state].epsilon.iter() {
+                if !states.contains(next) {
+                    states.push(*next);
+                    stack.push(*next);
+                }
+            }
+        }
+
+        states.sort();
+        states.dedup();
+    }
+}
+
+/// A regular expression with a finite language, from which strings can be sampled uniformly.
+///
+/// The expression is compiled to a deterministic automaton, in which every string of the language
+/// corresponds to exactly one path. Since the language is finite the automaton is acyclic,
+/// so the number of strings accepted from each state can be counted exactly and used to weight every step.
+pub struct Pattern {
+    accepting: Vec<bool>,
+    transitions: Vec<Vec<(u8, usize)>>,
+    counts: Vec<BigUint>
+}
+
+impl Pattern {
+    /// Compiles a regular expression, refusing any pattern with unbounded repetition.
+    pub fn parse<'a>(source: &str) -> PatternResult<'a> {
+        let mut body = source.strip_prefix('^').unwrap_or(source);
+
+        // A trailing `$` is an anchor unless it is escaped by an odd number of backslashes.
+        if let Some(rest) = body.strip_suffix('$') {
+            if (rest.len() - rest.trim_end_matches('\\').len()) % 2 == 0 { body = rest; }
+        }
+
+        let mut parser = Parser { characters: body.chars().collect(), position: 0, source };
+        let node = parser.parse_alternation()?;
+
+        if parser.position < parser.characters.len() { return Err(parser.error("Unbalanced ')'")); }
+
+        let mut nfa = Nfa::default();
+        let (start, end) = nfa.build(&node)?;
+
+        let mut initial = vec![start];
+
+        nfa.closure(&mut initial);
+
+        let mut sets = vec![initial.clone()];
+        let mut indices = HashMap::from([(initial, 0)]);
+        let mut transitions = Vec::<Vec<(u8, usize)>>::new();
+        let mut queue = VecDeque::from([0]);
+
+        // Determinize the automaton with the subset construction, visiting the states in the order they are created.
+        while let Some(index) = queue.pop_front() {
+            let mut targets = Vec::new();
+
+            for character in FIRST..=LAST {
+                let mut next = sets[index].iter()
+                    .filter_map(|state| nfa.states[*state].transition)
+                    .filter(|(set, _)| set & (1 << character) != 0)
+                    .map(|(_, target)| target)
+                    .collect::<Vec<_>>();
+
+                if next.is_empty() { continue; }
+
+                nfa.closure(&mut next);
+
+                let target = match indices.get(&next) {
+                    Some(target) => *target,
+                    None => {
+                        if sets.len() >= STATE_LIMIT { return Err("The pattern is too complex".into()); }
+
+                        sets.push(next.clone());
+                        indices.insert(next, sets.len() - 1);
+                        queue.push_back(sets.len() - 1);
+
+                        sets.len() - 1
+                    }
+                };
+
+                targets.push((character, target));
+            }
+
+            transitions.push(targets);
+        }
+
+        let accepting = sets.iter().map(|set| set.contains(&end)).collect::<Vec<_>>();
+        let counts = count_paths(&accepting, &transitions);
+
+        if counts[0].is_zero() { return Err(format!("The pattern {source:?} does not match any string").into()); }
+
+        Ok(Pattern { accepting, transitions, counts })
+    }
+
+    /// The number of distinct strings matched by the pattern.
+    pub fn keyspace(&self) -> &BigUint {
+        &self.counts[0]
+    }
+}
+
+/// Counts the strings accepted from every state of an acyclic automaton.
+fn count_paths(accepting: &[bool], transitions: &[Vec<(u8, usize)>]) -> Vec<BigUint> {
+    let size = accepting.len();
+    let mut order = Vec::with_capacity(size);
+    let mut visited = vec![false; size];
+    let mut stack = vec![(0, 0)];
+
+    visited[0] = true;
+
+    // Order the states so that every state comes after all of its successors.
+    while let Some((state, edge)) = stack.pop() {
+        match transitions[state].get(edge) {
+            Some((_, target)) => {
+                stack.push((state, edge + 1));
+
+                if !visited[*target] {
+                    visited[*target] = true;
+                    stack.push((*target, 0));
+                }
+            },
+            None => order.push(state)
+        }
+    }
+
+    let mut counts = vec![BigUint::zero(); size];
+
+    for state in order {
+        let mut count = if accepting[state] { BigUint::one() } else { BigUint::zero() };
+
+        for (_, target) in transitions[state].iter() {
+            count += &counts[*target];
+        }
+
+        counts[state] = count;
+    }
+
+    counts
+}
+
+/// Generate a random string matching a pattern, chosen uniformly from every string the pattern matches.
+pub fn generate_pattern<R: Rng + Sized>(pattern: &Pattern, rng: &mut R) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut state = 0;
+
+    loop {
+        let mut choice = rng.gen_biguint_below(&pattern.counts[state]);
+
+        if pattern.accepting[state] {
+            if choice.is_zero() { break; }
+
+            choice -= 1_u32;
+        }
+
+        for (character, target) in pattern.transitions[state].iter() {
+            if choice < pattern.counts[*target] {
+                output.push(*character);
+                state = *target;
+                break;
+            }
+
+            choice -= &pattern.counts[*target];
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rand::thread_rng;
+    use regex::Regex;
+
+    use super::*;
+
+    fn assert_matches(source: &str) {
+        let pattern = Pattern::parse(source).unwrap();
+        let regex = Regex::new(&format!("^(?:{})$", source.trim_start_matches('^').trim_end_matches('$'))).unwrap();
+        let rng = &mut thread_rng();
+
+        for _ in 0..100 {
+            let string = String::from_utf8(generate_pattern(&pattern, rng)).unwrap();
+
+            assert!(regex.is_match(&string), "{string:?} does not match {source:?}");
+        }
+    }
+
+    #[test]
+    fn generates_matching_strings() {
+        assert_matches(r"^[A-Z]{3}-\d{6}$");
+        assert_matches(r"(?:foo|ba[rz]){1,3}");
+        assert_matches(r"[^a-z\d]{4}x?");
+        assert_matches(r"\w{2,5}\.(com|org)");
+        assert_matches(r"[-a\]]{3}");
+    }
+
+    #[test]
+    fn counts_distinct_strings() {
+        assert_eq!(*Pattern::parse(r"[A-Z]{3}-\d{6}").unwrap().keyspace(), BigUint::from(26_u64.pow(3) * 10_u64.pow(6)));
+
+        // Ambiguous alternatives are only counted once.
+        assert_eq!(*Pattern::parse(r"a|a|ab?").unwrap().keyspace(), BigUint::from(2_u32));
+        assert_eq!(*Pattern::parse(r"a{0,2}a{0,2}").unwrap().keyspace(), BigUint::from(5_u32));
+    }
+
+    #[test]
+    fn samples_uniformly() {
+        // The strings `a`, `aa` and `ab` are equally likely, even though `a` can be matched in two ways.
+        let pattern = Pattern::parse(r"a|a|ab?|aa").unwrap();
+        let mut histogram = HashMap::<Vec<u8>, usize>::new();
+        let rng = &mut thread_rng();
+
+        for _ in 0..9000 {
+            *histogram.entry(generate_pattern(&pattern, rng)).or_default() += 1;
+        }
+
+        assert_eq!(histogram.len(), 3);
+        assert!(histogram.values().all(|count| (2700..3300).contains(count)), "{histogram:?}");
+    }
+
+    #[test]
+    fn refuses_unbounded_repetition() {
+        assert!(Pattern::parse(r"a*").is_err());
+        assert!(Pattern::parse(r"a+").is_err());
+        assert!(Pattern::parse(r"a{2,}").is_err());
+    }
+
+    #[test]
+    fn refuses_invalid_patterns() {
+        assert!(Pattern::parse(r"(ab").is_err());
+        assert!(Pattern::parse(r"ab)").is_err());
+        assert!(Pattern::parse(r"[ab").is_err());
+        assert!(Pattern::parse(r"a{3,1}").is_err());
+        assert!(Pattern::parse(r"\q").is_err());
+        assert!(Pattern::parse(r"[^ -~]").is_err());
+    }
+}
@@ -0,0 +1,560 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+use rand::Rng;
+
+use crate::entropy::{keyspace_bits, shortest_length, uniform_bits};
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type SamplerResult<'a> = Result<PasswordSampler, BoxedError<'a>>;
+
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!@*-_.";
+
+/// Characters which are easily mistaken for one another.
+const LOOKALIKES: &str = "0O1lI";
+
+/// The largest number of counts the sampler may tabulate before a policy is considered too complex.
+const TABLE_LIMIT: usize = 1 << 22;
+
+/// A class of characters which a password policy can require a minimum number of.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CharacterClass {
+    Upper,
+    Lower,
+    Digit,
+    Symbol
+}
+
+impl CharacterClass {
+    const ALL: [CharacterClass; 4] = [CharacterClass::Upper, CharacterClass::Lower, CharacterClass::Digit, CharacterClass::Symbol];
+
+    /// Classifies a character.
+    pub fn of(character: char) -> Self {
+        if character.is_uppercase() { CharacterClass::Upper }
+        else if character.is_lowercase() { CharacterClass::Lower }
+        else if character.is_ascii_digit() { CharacterClass::Digit }
+        else { CharacterClass::Symbol }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// The rules which every password generated under a policy must follow.
+#[derive(Debug, Clone, Default)]
+pub struct PasswordPolicy {
+    /// Exclude the digits from the character set.
+    pub no_digits: bool,
+    /// Exclude the default symbols from the character set.
+    pub no_symbols: bool,
+    /// The minimum number of characters required from each class, in the order upper, lower, digit, symbol.
+    pub minimums: [usize; 4],
+    /// Characters to add to the character set.
+    pub allowed: String,
+    /// Characters to remove from the character set.
+    pub forbidden: String,
+    /// Remove characters which are easily mistaken for one another.
+    pub no_lookalikes: bool,
+    /// The longest permitted run of identical characters.
+    pub max_repeat: Option<usize>,
+    /// The longest permitted run of ascending or descending characters, such as `abc` or `321`.
+    pub max_sequence: Option<usize>
+}
+
+impl PasswordPolicy {
+    /// Gets the character set of every class, sorted and without duplicates.
+    pub fn character_classes(&self) -> [Vec<char>; 4] {
+        let mut classes: [Vec<char>; 4] = Default::default();
+        let defaults = [
+            UPPERCASE,
+            LOWERCASE,
+            if self.no_digits { "" } else { DIGITS },
+            if self.no_symbols { "" } else { SYMBOLS }
+        ];
+        let characters = defaults.iter()
+            .flat_map(|value| value.chars())
+            .chain(self.allowed.chars())
+            .filter(|character| !character.is_control())
+            .filter(|character| !self.forbidden.contains(*character))
+            .filter(|character| !(self.no_lookalikes && LOOKALIKES.contains(*character)));
+
+        for character in characters {
+            classes[CharacterClass::of(character).index()].push(character);
+        }
+
+        for class in classes.iter_mut() {
+            class.sort();
+            class.dedup();
+        }
+
+        classes
+    }
+
+    /// Gets the full character set of the policy.
+    pub fn character_set(&self) -> Vec<char> {
+        self.character_classes().concat()
+    }
+
+    /// Whether the policy constrains the composition of a password beyond its character set.
+    pub fn is_constrained(&self) -> bool {
+        self.minimums.iter().any(|minimum| *minimum > 0) || self.max_repeat.is_some() || self.max_sequence.is_some()
+    }
+}
+
+/// The run of identical or sequential characters that ends a partial password.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Run {
+    /// The last character does not continue a run.
+    Single,
+    /// The last character was repeated this many times in a row.
+    Repeat(usize),
+    /// The last character ends an ascending sequence of this length.
+    Ascending(usize),
+    /// The last character ends a descending sequence of this length.
+    Descending(usize)
+}
+
+/// Samples passwords uniformly from every password of a fixed length which satisfies a policy.
+///
+/// The sampler counts how many valid completions follow every reachable state of a partial password,
+/// where a state is made up of the number of characters drawn from each class (capped at the class minimum)
+/// and the run which ends the partial password. Each character is then drawn with a weight equal to
+/// the number of valid passwords that start with it, so every valid password is equally likely,
+/// and no candidate is ever rejected.
+pub struct PasswordSampler {
+    alphabet: Vec<char>,
+    classes: Vec<usize>,
+    minimums: [usize; 4],
+    max_repeat: Option<usize>,
+    max_sequence: Option<usize>,
+    runs: Vec<Run>,
+    width: usize,
+    tables: Vec<Vec<BigUint>>
+}
+
+impl PasswordSampler {
+    /// Creates a sampler for passwords of the given length.
+    pub fn new<'a>(policy: &PasswordPolicy, length: usize) -> SamplerResult<'a> {
+        let character_classes = policy.character_classes();
+        let minimums = policy.minimums;
+
+        for class in CharacterClass::ALL {
+            if minimums[class.index()] > 0 && character_classes[class.index()].is_empty() {
+                return Err(format!("The policy requires {class:?} characters, but none are allowed").into());
+            }
+        }
+
+        let alphabet = character_classes.concat();
+        let classes = character_classes.iter()
+            .enumerate()
+            .flat_map(|(index, class)| class.iter().map(move |_| index))
+            .collect::<Vec<_>>();
+
+        if alphabet.is_empty() { return Err("The policy does not allow any characters".into()); }
+
+        if policy.max_repeat == Some(0) || policy.max_sequence == Some(0) {
+            return Err("The longest permitted run of characters must be at least one".into());
+        }
+
+        let required = minimums.iter().sum::<usize>();
+
+        if required > length {
+            return Err(format!("The policy requires at least {required} characters, but the length is {length}").into());
+        }
+
+        let mut runs = vec![Run::Single];
+
+        if let Some(maximum) = policy.max_repeat { runs.extend((2..=maximum).map(Run::Repeat)); }
+
+        if let Some(maximum) = policy.max_sequence {
+            runs.extend((2..=maximum).map(Run::Ascending));
+            runs.extend((2..=maximum).map(Run::Descending));
+        }
+
+        // Without any run constraints, the last character has no bearing on what may follow it.
+        let tracked = policy.max_repeat.is_some() || policy.max_sequence.is_some();
+        let width = if tracked { 1 + alphabet.len() * runs.len() } else { 1 };
+        let combinations = minimums.iter().map(|minimum| minimum + 1).product::<usize>();
+
+        if (length + 1).saturating_mul(combinations).saturating_mul(width) > TABLE_LIMIT {
+            return Err("The policy is too complex to sample for this length".into());
+        }
+
+        let mut sampler = PasswordSampler {
+            alphabet,
+            classes,
+            minimums,
+            max_repeat: policy.max_repeat,
+            max_sequence: policy.max_sequence,
+            runs,
+            width,
+            tables: Vec::with_capacity(length + 1)
+        };
+
+        sampler.tabulate(length, combinations);
+
+        if sampler.keyspace().is_zero() {
+            return Err(format!("The policy cannot be satisfied by a password of {length} characters").into());
+        }
+
+        Ok(sampler)
+    }
+
+    /// Creates a sampler for the shortest passwords which provide at least [bits] of entropy.
+    pub fn for_bits<'a>(policy: &PasswordPolicy, bits: usize) -> SamplerResult<'a> {
+        let choices = policy.character_set().len();
+
+        // The constraints can only shrink the keyspace, so the unconstrained length is a lower bound.
+        let mut length = match shortest_length(bits, |length| uniform_bits(choices, length)) {
+            Some(length) => length.max(policy.minimums.iter().sum()),
+            None => return Err("The policy must allow at least two characters to reach an entropy target".into())
+        };
+        let mut previous = BigUint::zero();
+
+        loop {
+            let sampler = PasswordSampler::new(policy, length)?;
+
+            if keyspace_bits(sampler.keyspace()) >= bits as f64 { return Ok(sampler); }
+
+            if *sampler.keyspace() <= previous {
+                return Err(format!("The policy cannot produce passwords with {bits} bits of entropy").into());
+            }
+
+            previous = sampler.keyspace().clone();
+            length += 1;
+        }
+    }
+
+    /// The number of distinct passwords which satisfy the policy.
+    pub fn keyspace(&self) -> &BigUint {
+        &self.tables[self.tables.len() - 1][0]
+    }
+
+    /// The length of the passwords produced by this sampler.
+    pub fn length(&self) -> usize {
+        self.tables.len() - 1
+    }
+
+    /// Counts the valid completions of every state, for every number of remaining characters.
+    fn tabulate(&mut self, length: usize, combinations: usize) {
+        let size = combinations * self.width;
+        let mut table = vec![BigUint::zero(); size];
+
+        // With nothing left to draw, a state is valid only if every minimum has been met.
+        let satisfied = combinations - 1;
+
+        for last in 0..self.width {
+            table[satisfied * self.width + last] = BigUint::one();
+        }
+
+        self.tables.push(table);
+
+        for _ in 0..length {
+            let previous = &self.tables[self.tables.len() - 1];
+
+            // The completions of every state that follows a character which does not continue a run, by class.
+            let mut sums = vec![[BigUint::zero(), BigUint::zero(), BigUint::zero(), BigUint::zero()]; combinations];
+
+            for (counts, sum) in sums.iter_mut().enumerate() {
+                for (character, class) in self.classes.iter().enumerate() {
+                    sum[*class] += &previous[self.state(counts, Some((character, Run::Single)))];
+                }
+            }
+
+            let mut table = vec![BigUint::zero(); size];
+
+            for counts in 0..combinations {
+                for last in 0..self.width {
+                    let mut total = BigUint::zero();
+
+                    for class in 0..4 {
+                        total += &sums[self.increment(counts, class)][class];
+                    }
+
+                    // Correct the characters which would extend the run instead of starting a new one.
+                    if let Some((character, run)) = self.decode_last(last) {
+                        for (next, following) in self.continuations(character, run) {
+                            let counts = self.increment(counts, self.classes[next]);
+
+                            total -= &previous[self.state(counts, Some((next, Run::Single)))];
+
+                            if let Some(following) = following {
+                                total += &previous[self.state(counts, Some((next, following)))];
+                            }
+                        }
+                    }
+
+                    table[counts * self.width + last] = total;
+                }
+            }
+
+            self.tables.push(table);
+        }
+    }
+
+    /// Gets the characters which would extend the given run, and the run that would follow each,
+    /// or `None` if the character would make the run too long.
+    fn continuations(&self, character: usize, run: Run) -> Vec<(usize, Option<Run>)> {
+        let mut continuations = Vec::new();
+
+        if let Some(maximum) = self.max_repeat {
+            let length = match run { Run::Repeat(length) => length + 1, _ => 2 };
+
+            continuations.push((character, (length <= maximum).then_some(Run::Repeat(length))));
+        }
+
+        if let Some(maximum) = self.max_sequence {
+            let current = self.alphabet[character];
+            let neighbours = [
+                (char::from_u32(current as u32 + 1), true),
+                ((current as u32).checked_sub(1).and_then(char::from_u32), false)
+            ];
+
+            for (neighbour, ascending) in neighbours {
+                let Some(neighbour) = neighbour else { continue; };
+
+                if !current.is_ascii_alphanumeric() || CharacterClass::of(neighbour) != CharacterClass::of(current) { continue; }
+
+                let Some(next) = self.alphabet.iter().position(|value| *value == neighbour) else { continue; };
+
+                let following = match (run, ascending) {
+                    (Run::Ascending(length), true) => Run::Ascending(length + 1),
+                    (Run::Descending(length), false) => Run::Descending(length + 1),
+                    (_, true) => Run::Ascending(2),
+                    (_, false) => Run::Descending(2)
+                };
+                let length = match following { Run::Ascending(length) | Run::Descending(length) => length, _ => 1 };
+
+                continuations.push((next, (length <= maximum).then_some(following)));
+            }
+        }
+
+        continuations
+    }
+
+    /// Gets the index of the state made up of the class counts and the last character, if any.
+    fn state(&self, counts: usize, last: Option<(usize, Run)>) -> usize {
+        let last = match last {
+            Some(_) if self.width == 1 => 0,
+            Some((character, run)) => {
+                let run = self.runs.iter().position(|value| *value == run).unwrap();
+
+                1 + character * self.runs.len() + run
+            },
+            None => 0
+        };
+
+        counts * self.width + last
+    }
+
+    /// Gets the last character and run of a state, if any are tracked.
+    fn decode_last(&self, last: usize) -> Option<(usize, Run)> {
+        if last == 0 { return None; }
+
+        let index = last - 1;
+
+        Some((index / self.runs.len(), self.runs[index % self.runs.len()]))
+    }
+
+    /// Adds a character of the given class to the class counts, saturating at the class minimum.
+    fn increment(&self, counts: usize, class: usize) -> usize {
+        let mut radix = 1;
+
+        for index in 0..class { radix *= self.minimums[index] + 1; }
+
+        let count = (counts / radix) % (self.minimums[class] + 1);
+
+        if count < self.minimums[class] { counts + radix } else { counts }
+    }
+
+    /// Gets the state which follows a state once the character at [next] is drawn, if the policy permits it.
+    fn advance(&self, state: usize, next: usize) -> Option<usize> {
+        let (counts, last) = (state / self.width, state % self.width);
+        let counts = self.increment(counts, self.classes[next]);
+
+        if let Some((character, run)) = self.decode_last(last) {
+            if let Some((_, following)) = self.continuations(character, run).into_iter().find(|(value, _)| *value == next) {
+                return following.map(|following| self.state(counts, Some((next, following))));
+            }
+        }
+
+        Some(self.state(counts, Some((next, Run::Single))))
+    }
+}
+
+/// Generate a password which satisfies the policy of the [sampler].
+pub fn generate_constrained_password<R: Rng + Sized>(sampler: &PasswordSampler, rng: &mut R) -> Vec<u8> {
+    let length = sampler.length();
+    let mut output = String::with_capacity(length);
+    let mut state = 0;
+
+    for remaining in (1..=length).rev() {
+        let table = &sampler.tables[remaining - 1];
+        let mut choice = rng.gen_biguint_below(&sampler.tables[remaining][state]);
+
+        for (index, character) in sampler.alphabet.iter().enumerate() {
+            let Some(next) = sampler.advance(state, index) else { continue; };
+
+            if choice < table[next] {
+                output.push(*character);
+                state = next;
+                break;
+            }
+
+            choice -= &table[next];
+        }
+    }
+
+    output.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rand::thread_rng;
+
+    use super::*;
+
+    fn count_class(password: &str, class: CharacterClass) -> usize {
+        password.chars().filter(|character| CharacterClass::of(*character) == class).count()
+    }
+
+    fn longest_run(password: &str, step: i32) -> usize {
+        let characters = password.chars().collect::<Vec<_>>();
+        let mut longest = 1;
+        let mut current = 1;
+
+        for pair in characters.windows(2) {
+            let continues = pair[1] as i32 - pair[0] as i32 == step
+                && (step == 0 || (pair[0].is_ascii_alphanumeric() && CharacterClass::of(pair[0]) == CharacterClass::of(pair[1])));
+
+            current = if continues { current + 1 } else { 1 };
+            longest = longest.max(current);
+        }
+
+        longest
+    }
+
+    #[test]
+    fn satisfies_minimum_counts() {
+        let policy = PasswordPolicy { minimums: [2, 2, 3, 1], ..Default::default() };
+        let sampler = PasswordSampler::new(&policy, 8).unwrap();
+
+        for _ in 0..100 {
+            let bytes = generate_constrained_password(&sampler, &mut thread_rng());
+            let password = String::from_utf8(bytes).unwrap();
+
+            assert_eq!(password.chars().count(), 8);
+            assert!(count_class(&password, CharacterClass::Upper) >= 2);
+            assert!(count_class(&password, CharacterClass::Lower) >= 2);
+            assert!(count_class(&password, CharacterClass::Digit) >= 3);
+            assert!(count_class(&password, CharacterClass::Symbol) >= 1);
+        }
+    }
+
+    #[test]
+    fn rejects_runs() {
+        let policy = PasswordPolicy {
+            no_symbols: true,
+            allowed: "".to_string(),
+            forbidden: LOWERCASE.to_string() + UPPERCASE + "56789",
+            max_repeat: Some(2),
+            max_sequence: Some(2),
+            ..Default::default()
+        };
+        let sampler = PasswordSampler::new(&policy, 64).unwrap();
+
+        for _ in 0..100 {
+            let bytes = generate_constrained_password(&sampler, &mut thread_rng());
+            let password = String::from_utf8(bytes).unwrap();
+
+            assert!(longest_run(&password, 0) <= 2, "{password}");
+            assert!(longest_run(&password, 1) <= 2, "{password}");
+            assert!(longest_run(&password, -1) <= 2, "{password}");
+        }
+    }
+
+    #[test]
+    fn counts_keyspace_exactly() {
+        // Two digits without a repeated character: 10 * 9 passwords.
+        let policy = PasswordPolicy { forbidden: LOWERCASE.to_string() + UPPERCASE + SYMBOLS, max_repeat: Some(1), ..Default::default() };
+        let sampler = PasswordSampler::new(&policy, 2).unwrap();
+
+        assert_eq!(*sampler.keyspace(), BigUint::from(90_u32));
+
+        // Three characters from `abc` without a sequence longer than two: every password except `abc` and `cba`.
+        let policy = PasswordPolicy {
+            no_digits: true,
+            no_symbols: true,
+            forbidden: UPPERCASE.to_string() + "defghijklmnopqrstuvwxyz",
+            max_sequence: Some(2),
+            ..Default::default()
+        };
+        let sampler = PasswordSampler::new(&policy, 3).unwrap();
+
+        assert_eq!(*sampler.keyspace(), BigUint::from(25_u32));
+    }
+
+    #[test]
+    fn samples_uniformly() {
+        // Two characters from `ab1` with at least one digit: `1a`, `1b`, `11`, `a1`, `b1`.
+        let policy = PasswordPolicy {
+            no_symbols: true,
+            minimums: [0, 0, 1, 0],
+            forbidden: UPPERCASE.to_string() + "cdefghijklmnopqrstuvwxyz023456789",
+            ..Default::default()
+        };
+        let sampler = PasswordSampler::new(&policy, 2).unwrap();
+        let mut histogram = HashMap::<Vec<u8>, usize>::new();
+        let rng = &mut thread_rng();
+
+        for _ in 0..10000 {
+            *histogram.entry(generate_constrained_password(&sampler, rng)).or_default() += 1;
+        }
+
+        assert_eq!(histogram.len(), 5);
+        assert!(histogram.values().all(|count| (1700..2300).contains(count)), "{histogram:?}");
+    }
+
+    #[test]
+    fn excludes_lookalikes_and_forbidden_characters() {
+        let policy = PasswordPolicy { no_lookalikes: true, forbidden: "xyz".to_string(), allowed: "#".to_string(), ..Default::default() };
+        let character_set = policy.character_set();
+
+        assert!(!character_set.iter().any(|character| "0O1lIxyz".contains(*character)));
+        assert!(character_set.contains(&'#'));
+    }
+
+    #[test]
+    fn reports_unsatisfiable_policies() {
+        let policy = PasswordPolicy { minimums: [4, 4, 4, 4], ..Default::default() };
+
+        assert!(PasswordSampler::new(&policy, 12).is_err());
+
+        let policy = PasswordPolicy { no_digits: true, minimums: [0, 0, 1, 0], ..Default::default() };
+
+        assert!(PasswordSampler::new(&policy, 12).is_err());
+
+        let policy = PasswordPolicy { forbidden: LOWERCASE.to_string() + UPPERCASE + SYMBOLS + "123456789", max_repeat: Some(3), ..Default::default() };
+
+        assert!(PasswordSampler::new(&policy, 4).is_err());
+    }
+
+    #[test]
+    fn finds_shortest_length_for_bits() {
+        let policy = PasswordPolicy { minimums: [1, 1, 1, 1], max_repeat: Some(2), ..Default::default() };
+        let sampler = PasswordSampler::for_bits(&policy, 80).unwrap();
+        let shorter = PasswordSampler::new(&policy, sampler.length() - 1).unwrap();
+
+        assert!(keyspace_bits(sampler.keyspace()) >= 80.0);
+        assert!(keyspace_bits(shorter.keyspace()) < 80.0);
+
+        let policy = PasswordPolicy { forbidden: LOWERCASE.to_string() + UPPERCASE + SYMBOLS + "23456789", max_repeat: Some(1), ..Default::default() };
+
+        assert!(PasswordSampler::for_bits(&policy, 8).is_err());
+    }
+}
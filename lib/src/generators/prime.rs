@@ -0,0 +1,350 @@
+use clap::ValueEnum;
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::{One, ToPrimitive, Zero};
+use rand::{CryptoRng, Rng};
+use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+
+use crate::random::RandomSource;
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type OptionsResult<'a> = Result<PrimeOptions, BoxedError<'a>>;
+type PrimeResult<'a> = Result<BigUint, BoxedError<'a>>;
+
+/// Candidates are divided by every prime below this before Miller-Rabin is tried, which rejects most of them cheaply.
+const TRIAL_DIVISION_LIMIT: u64 = 4096;
+
+/// The number of Miller-Rabin rounds, each of which passes a composite number with a probability of at most 1/4.
+const MILLER_RABIN_ROUNDS: usize = 40;
+
+/// How many times more candidates than a prime is expected to need are drawn before the search gives up, since some
+/// options, such as a residue with only a few candidates of the given size, can't be satisfied by any prime.
+const SEARCH_LIMIT_FACTOR: f64 = 1000.0;
+
+/// The fewest candidates drawn before the search gives up, which keeps the limit generous for small primes.
+const MIN_SEARCH_LIMIT: usize = 65536;
+
+/// The generator of Diffie-Hellman parameters, which is what OpenSSL uses by default.
+pub const DH_GENERATOR: u32 = 2;
+
+/// How a prime or a set of Diffie-Hellman parameters is written.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum PrimeFormat {
+    #[default]
+    Decimal,
+    Hex,
+    /// PKCS #3 Diffie-Hellman parameters, as read by `openssl dhparam`.
+    Pem
+}
+
+/// What a generated prime must satisfy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrimeOptions {
+    bits: u64,
+    safe: bool,
+    remainder: BigUint,
+    modulus: BigUint
+}
+
+impl PrimeOptions {
+    /// Require a prime of exactly [bits] bits which, if [safe], is `2q + 1` for another prime `q`, and which is
+    /// congruent to [remainder] modulo [modulus] when a residue is given.
+    pub fn new<'a>(bits: u64, safe: bool, residue: Option<(BigUint, BigUint)>) -> OptionsResult<'a> {
+        let (remainder, modulus) = residue.unwrap_or((BigUint::one(), BigUint::from(2_u32)));
+
+        if bits < 3 { return Err("A prime must have at least 3 bits".into()); }
+        if modulus.is_zero() { return Err("The modulus must not be zero".into()); }
+        if remainder >= modulus { return Err("The remainder must be less than the modulus".into()); }
+        if !remainder.gcd(&modulus).is_one() {
+            return Err("The remainder and the modulus must be coprime, or no prime could satisfy them".into());
+        }
+        if modulus.bits() >= bits { return Err(format!("The modulus must have fewer than {bits} bits").into()); }
+
+        // A safe prime is 3 modulo 4, so q is odd, which a residue modulo a multiple of 4 may rule out.
+        if safe && (&modulus % 4_u32).is_zero() && &remainder % 4_u32 != BigUint::from(3_u32) {
+            return Err("A safe prime is 3 modulo 4, which the residue rules out".into());
+        }
+
+        Ok(PrimeOptions { bits, safe, remainder, modulus })
+    }
+
+    /// Require a safe prime of [bits] bits which is 23 modulo 24, so that 2 generates its subgroup of order `q`.
+    pub fn diffie_hellman<'a>(bits: u64) -> OptionsResult<'a> {
+        PrimeOptions::new(bits, true, Some((BigUint::from(23_u32), BigUint::from(24_u32))))
+    }
+
+    /// Roughly how many numbers near 2^bits there are for each prime, or each safe prime, among them.
+    fn spacing(&self) -> f64 {
+        let logarithm = self.bits as f64 * std::f64::consts::LN_2;
+
+        match self.safe {
+            true => logarithm * logarithm,
+            false => logarithm
+        }
+    }
+
+    /// Roughly the number of bits of entropy in a generated prime, from the density of primes near 2^bits.
+    pub fn entropy(&self) -> f64 {
+        (self.bits - 1) as f64 - self.spacing().log2()
+    }
+
+    /// The number of candidates drawn before the search gives up, which is far more than a prime needs.
+    fn search_limit(&self) -> usize {
+        ((SEARCH_LIMIT_FACTOR * self.spacing()) as usize).max(MIN_SEARCH_LIMIT)
+    }
+}
+
+/// The primes below [TRIAL_DIVISION_LIMIT], grouped so the product of each group fits in 64 bits.
+struct SmallPrimes {
+    groups: Vec<(u64, Vec<u64>)>
+}
+
+impl SmallPrimes {
+    fn new() -> Self {
+        let mut composite = vec![false; TRIAL_DIVISION_LIMIT as usize];
+        let mut groups: Vec<(u64, Vec<u64>)> = Vec::new();
+
+        for number in 2..TRIAL_DIVISION_LIMIT {
+            if composite[number as usize] { continue; }
+
+            for multiple in (number * number..TRIAL_DIVISION_LIMIT).step_by(number as usize) {
+                composite[multiple as usize] = true;
+            }
+
+            match groups.last_mut().and_then(|(product, primes)| product.checked_mul(number).map(|next| (product, primes, next))) {
+                Some((product, primes, next)) => {
+                    *product = next;
+                    primes.push(number);
+                },
+                None => groups.push((number, vec![number]))
+            }
+        }
+
+        SmallPrimes { groups }
+    }
+
+    /// Whether a candidate, or half of one less than it when [safe], has a small prime factor other than itself.
+    fn divides(&self, candidate: &BigUint, safe: bool) -> bool {
+        let small = candidate.to_u64();
+
+        self.groups.iter().any(|(product, primes)| {
+            let remainder = (candidate % product).to_u64().unwrap();
+
+            primes.iter().any(|&prime| {
+                let residue = remainder % prime;
+
+                // If the candidate is 1 modulo an odd prime, then the prime divides q = (candidate - 1) / 2.
+                (residue == 0 && small != Some(prime)) || (safe && prime != 2 && residue == 1 && small != Some(2 * prime + 1))
+            })
+        })
+    }
+}
+
+/// Test whether [number] is prime with Miller-Rabin, using the given number of random bases.
+pub fn is_probable_prime<R: Rng + Sized>(number: &BigUint, rounds: usize, rng: &mut R) -> bool {
+    let two = BigUint::from(2_u32);
+
+    if *number < two { return false; }
+    if *number < BigUint::from(4_u32) { return true; }
+    if number.is_even() { return false; }
+
+    let minus_one = number - 1_u32;
+    let shift = minus_one.trailing_zeros().unwrap();
+    let odd = &minus_one >> shift;
+
+    (0..rounds).all(|_| {
+        let base = rng.gen_biguint_range(&two, &minus_one);
+        let mut value = base.modpow(&odd, number);
+
+        if value.is_one() || value == minus_one { return true; }
+
+        for _ in 1..shift {
+            value = value.modpow(&two, number);
+
+            if value == minus_one { return true; }
+        }
+
+        false
+    })
+}
+
+/// Build a random candidate with the right number of bits and residue, or nothing if adjusting it overflows.
+fn candidate<R: Rng + Sized>(options: &PrimeOptions, rng: &mut R) -> Option<BigUint> {
+    let mut number = rng.gen_biguint(options.bits);
+
+    number.set_bit(options.bits - 1, true);
+    number += (&options.remainder + &options.modulus - (&number % &options.modulus)) % &options.modulus;
+
+    // Adding the modulus keeps the residue while stepping through the residues modulo 4, unless it is a multiple of 4.
+    for _ in 0..4 {
+        if !options.safe || (&number % 4_u32) == BigUint::from(3_u32) { break; }
+
+        number += &options.modulus;
+    }
+
+    let valid = number.bits() == options.bits && (!options.safe || (&number % 4_u32) == BigUint::from(3_u32));
+
+    valid.then_some(number)
+}
+
+/// Generate a random prime which satisfies the options.
+///
+/// Candidates are tested in parallel, but the first one which is prime in the order they are drawn is always chosen,
+/// so the prime only depends on the random number generator. The search is an error once far more candidates have
+/// been drawn than a prime needs, which only happens when no prime satisfies the options.
+pub fn generate_prime<'a, R: Rng + CryptoRng + Sized>(options: &PrimeOptions, rng: &mut R) -> PrimeResult<'a> {
+    let source = RandomSource::from_seed(rng.gen());
+    let small_primes = SmallPrimes::new();
+    let limit = options.search_limit();
+
+    let prime = (0..limit)
+        .into_par_iter()
+        .by_exponential_blocks()
+        .find_map_first(|index| {
+            let mut rng = source.stream(index);
+            let number = candidate(options, &mut rng)?;
+
+            if small_primes.divides(&number, options.safe) { return None; }
+
+            let half = &number >> 1;
+
+            // A single round rejects nearly every composite, so the full test is only run on likely primes.
+            let prime = match options.safe {
+                true => is_probable_prime(&half, 1, &mut rng)
+                    && is_probable_prime(&number, MILLER_RABIN_ROUNDS, &mut rng)
+                    && is_probable_prime(&half, MILLER_RABIN_ROUNDS, &mut rng),
+                false => is_probable_prime(&number, MILLER_RABIN_ROUNDS, &mut rng)
+            };
+
+            prime.then_some(number)
+        });
+
+    match prime {
+        Some(prime) => Ok(prime),
+        None => Err(format!(
+            "No prime was found among {limit} candidates, so no {} of {} bits satisfies the options",
+            if options.safe { "safe prime" } else { "prime" },
+            options.bits
+        ).into())
+    }
+}
+
+/// Encode an unsigned integer as a DER INTEGER.
+fn der_integer(number: &BigUint) -> Vec<u8> {
+    let mut bytes = number.to_bytes_be();
+
+    // A set high bit would make the integer negative, so it is preceded by a zero byte.
+    if bytes[0] & 0x80 != 0 { bytes.insert(0, 0); }
+
+    [vec![0x02], der_length(bytes.len()), bytes].concat()
+}
+
+fn der_length(length: usize) -> Vec<u8> {
+    match length {
+        0..=0x7f => vec![length as u8],
+        _ => {
+            let bytes = length.to_be_bytes().into_iter().skip_while(|&byte| byte == 0).collect::<Vec<_>>();
+
+            [vec![0x80 | bytes.len() as u8], bytes].concat()
+        }
+    }
+}
+
+/// Write a prime in decimal or hexadecimal.
+pub fn encode_prime(prime: &BigUint, format: PrimeFormat) -> Vec<u8> {
+    match format {
+        PrimeFormat::Hex => format!("{prime:x}").into_bytes(),
+        _ => prime.to_string().into_bytes()
+    }
+}
+
+/// Write Diffie-Hellman parameters as PEM, or as the prime and the generator on their own lines.
+pub fn encode_dh_parameters(prime: &BigUint, generator: u32, format: PrimeFormat) -> Vec<u8> {
+    match format {
+        PrimeFormat::Pem => {
+            let body = [der_integer(prime), der_integer(&BigUint::from(generator))].concat();
+            let der = [vec![0x30], der_length(body.len()), body].concat();
+
+            pem::encode_config(&pem::Pem::new("DH PARAMETERS", der), pem::EncodeConfig::new().set_line_ending(pem::LineEnding::LF))
+                .trim_end()
+                .as_bytes()
+                .to_vec()
+        },
+        _ => [b"p = ".to_vec(), encode_prime(prime, format), format!("\ng = {generator}").into_bytes()].concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use crate::random::Csprng;
+
+    use super::*;
+
+    #[test]
+    fn tests_primality() {
+        let mersenne = (BigUint::one() << 127_usize) - 1_u32;
+        let carmichael = BigUint::from(561_u32);
+        let product = BigUint::from(2_147_483_647_u64 * 2_147_483_629);
+
+        assert!(is_probable_prime(&mersenne, MILLER_RABIN_ROUNDS, &mut thread_rng()));
+        assert!(!is_probable_prime(&carmichael, MILLER_RABIN_ROUNDS, &mut thread_rng()));
+        assert!(!is_probable_prime(&product, MILLER_RABIN_ROUNDS, &mut thread_rng()));
+        assert!(!is_probable_prime(&(&mersenne + 2_u32), MILLER_RABIN_ROUNDS, &mut thread_rng()));
+        assert!(SmallPrimes::new().divides(&BigUint::from(4095_u32 * 4093), false));
+        assert!(!SmallPrimes::new().divides(&BigUint::from(4093_u32), false));
+    }
+
+    #[test]
+    fn generates_primes_with_residue() {
+        let options = PrimeOptions::new(128, false, Some((BigUint::from(7_u32), BigUint::from(10_u32)))).unwrap();
+        let prime = generate_prime(&options, &mut thread_rng()).unwrap();
+
+        assert_eq!(prime.bits(), 128);
+        assert_eq!(&prime % 10_u32, BigUint::from(7_u32));
+        assert!(is_probable_prime(&prime, MILLER_RABIN_ROUNDS, &mut thread_rng()));
+        assert!(PrimeOptions::new(128, false, Some((BigUint::from(4_u32), BigUint::from(10_u32)))).is_err());
+        assert!(PrimeOptions::new(128, true, Some((BigUint::from(1_u32), BigUint::from(4_u32)))).is_err());
+    }
+
+    #[test]
+    fn generates_safe_primes() {
+        let prime = generate_prime(&PrimeOptions::diffie_hellman(128).unwrap(), &mut thread_rng()).unwrap();
+        let half = &prime >> 1;
+
+        assert_eq!(prime.bits(), 128);
+        assert_eq!(&prime % 24_u32, BigUint::from(23_u32));
+        assert!(is_probable_prime(&half, MILLER_RABIN_ROUNDS, &mut thread_rng()));
+        assert!(is_probable_prime(&prime, MILLER_RABIN_ROUNDS, &mut thread_rng()));
+    }
+
+    #[test]
+    fn gives_up_when_no_prime_satisfies_the_options() {
+        // The only 4-bit numbers which are 1 modulo 7 are 8 and 15.
+        let impossible = PrimeOptions::new(4, false, Some((BigUint::from(1_u32), BigUint::from(7_u32)))).unwrap();
+        let possible = PrimeOptions::new(4, true, None).unwrap();
+
+        assert!(generate_prime(&impossible, &mut thread_rng()).is_err());
+        assert_eq!(generate_prime(&possible, &mut thread_rng()).unwrap(), BigUint::from(11_u32));
+    }
+
+    #[test]
+    fn generates_reproducible_primes() {
+        let options = PrimeOptions::new(64, false, None).unwrap();
+        let first = generate_prime(&options, &mut <Csprng as rand::SeedableRng>::seed_from_u64(1)).unwrap();
+        let second = generate_prime(&options, &mut <Csprng as rand::SeedableRng>::seed_from_u64(1)).unwrap();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn encodes_dh_parameters() {
+        let prime = BigUint::from(0xffff_ffff_ffff_ffc5_u64);
+        let pem = String::from_utf8(encode_dh_parameters(&prime, DH_GENERATOR, PrimeFormat::Pem)).unwrap();
+        let der = pem::parse(&pem).unwrap().into_contents();
+
+        assert_eq!(hex::encode(der), "300e020900ffffffffffffffc5020102");
+        assert_eq!(encode_dh_parameters(&prime, DH_GENERATOR, PrimeFormat::Hex), b"p = ffffffffffffffc5\ng = 2");
+    }
+}
@@ -0,0 +1,149 @@
+use crc32fast::hash;
+use rand::Rng;
+
+use crate::generators::generate_password;
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type UnitResult<'a> = Result<(), BoxedError<'a>>;
+
+/// The digits, uppercase letters and lowercase letters, which is the alphabet of a token when none is given.
+pub const BASE62_ALPHABET: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// The number of random characters in a token when no length is given, which is what GitHub uses.
+pub const DEFAULT_TOKEN_LENGTH: usize = 30;
+
+/// The number of characters of the alphabet needed to write any CRC32 checksum.
+fn checksum_length(alphabet: &[char]) -> usize {
+    (1..).find(|&length| (alphabet.len() as f64).powi(length as i32) >= 2_f64.powi(32)).unwrap()
+}
+
+/// Write the CRC32 checksum of some text in the alphabet, with leading zeros so it always has the same length.
+fn encode_checksum(text: &str, alphabet: &[char]) -> String {
+    let base = alphabet.len() as u64;
+    let mut value = hash(text.as_bytes()) as u64;
+    let mut digits = vec![alphabet[0]; checksum_length(alphabet)];
+
+    for digit in digits.iter_mut().rev() {
+        *digit = alphabet[(value % base) as usize];
+        value /= base;
+    }
+
+    digits.into_iter().collect()
+}
+
+/// Check that an alphabet has at least two characters and none are repeated.
+pub fn check_token_alphabet<'a>(alphabet: &[char]) -> UnitResult<'a> {
+    if alphabet.len() < 2 { return Err("The alphabet of a token needs at least two characters".into()); }
+
+    if let Some(repeated) = alphabet.iter().enumerate().find_map(|(index, character)| alphabet[..index].contains(character).then_some(character)) {
+        return Err(format!("The character {repeated} appears more than once in the alphabet").into());
+    }
+
+    Ok(())
+}
+
+/// Generate a token in the style of GitHub, which is a prefix, a random body and a checksum.
+///
+/// The checksum is the CRC32 of the prefix and the body, written in the alphabet of the body, so a mistyped token
+/// can be recognised without looking it up.
+pub fn generate_token<R: Rng + Sized>(prefix: &str, alphabet: &[char], length: usize, rng: &mut R) -> Vec<u8> {
+    let mut token = prefix.to_string();
+
+    token.push_str(&String::from_utf8(generate_password(alphabet, length, rng)).unwrap());
+    token.push_str(&encode_checksum(&token, alphabet));
+    token.into_bytes()
+}
+
+/// Check the prefix, alphabet, length and checksum of a token created by [generate_token].
+///
+/// The checksum covers the prefix, so it can be checked without knowing the prefix. When one is given, the
+/// characters after it must all belong to the alphabet, and there must be `length` of them before the checksum.
+pub fn verify_token<'a>(token: &str, prefix: Option<&str>, alphabet: &[char], length: Option<usize>) -> UnitResult<'a> {
+    let token = token.trim();
+    let characters = token.chars().collect::<Vec<_>>();
+    let checksum_length = checksum_length(alphabet);
+
+    if characters.len() <= checksum_length { return Err("The token is too short to have a checksum".into()); }
+
+    let (start, body_length) = match prefix {
+        Some(prefix) => {
+            if !token.starts_with(prefix) { return Err(format!("The token doesn't begin with {prefix}").into()); }
+
+            let start = prefix.chars().count();
+
+            (start, characters.len().saturating_sub(start + checksum_length))
+        },
+        None => (characters.len() - checksum_length, 0)
+    };
+
+    if let Some(position) = characters[start..].iter().position(|character| !alphabet.contains(character)) {
+        return Err(format!("The character at position {} of the token isn't in the alphabet", start + position + 1).into());
+    }
+
+    if let (Some(expected), Some(_)) = (length, prefix) {
+        if body_length != expected {
+            return Err(format!("The token has {body_length} random characters, but {expected} were expected").into());
+        }
+    }
+
+    let (text, checksum) = characters.split_at(characters.len() - checksum_length);
+    let text = text.iter().collect::<String>();
+
+    if encode_checksum(&text, alphabet) != checksum.iter().collect::<String>() {
+        return Err("The checksum of the token doesn't match, so it was mistyped or altered".into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+    use regex::Regex;
+
+    use super::*;
+
+    fn base62() -> Vec<char> {
+        BASE62_ALPHABET.chars().collect()
+    }
+
+    #[test]
+    fn generates_github_style_tokens() {
+        let token = String::from_utf8(generate_token("acme_live_", &base62(), DEFAULT_TOKEN_LENGTH, &mut thread_rng())).unwrap();
+
+        assert!(Regex::new(r"^acme_live_[0-9A-Za-z]{36}$").unwrap().is_match(&token));
+        assert!(verify_token(&token, Some("acme_live_"), &base62(), Some(DEFAULT_TOKEN_LENGTH)).is_ok());
+        assert!(verify_token(&token, None, &base62(), None).is_ok());
+        assert!(verify_token(&token, Some("acme_test_"), &base62(), None).is_err());
+        assert!(verify_token(&token, Some("acme_live_"), &base62(), Some(32)).is_err());
+    }
+
+    #[test]
+    fn catches_mistyped_tokens() {
+        let token = String::from_utf8(generate_token("tok_", &base62(), 24, &mut thread_rng())).unwrap();
+        let mut characters = token.chars().collect::<Vec<_>>();
+
+        characters.swap(5, 6);
+
+        if characters[5] != characters[6] {
+            assert!(verify_token(&characters.iter().collect::<String>(), Some("tok_"), &base62(), None).is_err());
+        }
+
+        assert!(verify_token(&token.replace("tok_", "tak_"), None, &base62(), None).is_err());
+        assert!(verify_token(&format!("{token}!"), Some("tok_"), &base62(), None).is_err());
+    }
+
+    #[test]
+    fn uses_custom_alphabets() {
+        let hex = "0123456789abcdef".chars().collect::<Vec<_>>();
+        let token = String::from_utf8(generate_token("", &hex, 32, &mut thread_rng())).unwrap();
+
+        assert_eq!(checksum_length(&hex), 8);
+        assert_eq!(checksum_length(&base62()), 6);
+        assert_eq!(token.len(), 40);
+        assert!(verify_token(&token, Some(""), &hex, Some(32)).is_ok());
+        assert_eq!(&token[32..], format!("{:08x}", hash(&token.as_bytes()[..32])));
+        assert!(check_token_alphabet(&['a', 'b', 'a']).is_err());
+        assert!(check_token_alphabet(&['a']).is_err());
+    }
+}
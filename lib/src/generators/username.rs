@@ -1,5 +1,4 @@
-use rand::{random, thread_rng, Rng};
-use rand::rngs::ThreadRng;
+use rand::Rng;
 use rand::distributions::{Distribution, Standard};
 use rand::seq::SliceRandom;
 
@@ -15,27 +14,27 @@ const CONSONANTS: [char; 20] = [
     't', 'v', 'w', 'x', 'z'
 ];
 
-fn choose_random_vowel(rng: &mut ThreadRng) -> &char {
+fn choose_random_vowel<R: Rng + Sized>(rng: &mut R) -> &'static char {
     VOWELS.choose(rng).unwrap()
 }
 
-fn choose_random_consonant(rng: &mut ThreadRng) -> &char {
+fn choose_random_consonant<R: Rng + Sized>(rng: &mut R) -> &'static char {
     CONSONANTS.choose(rng).unwrap()
 }
 
-fn add_vowel(input: &mut Vec<char>, rng: &mut ThreadRng) {
+fn add_vowel<R: Rng + Sized>(input: &mut Vec<char>, rng: &mut R) {
     let value = choose_random_vowel(rng);
 
     input.push(*value);
 }
 
-fn add_consonant(input: &mut Vec<char>, rng: &mut ThreadRng) {
+fn add_consonant<R: Rng + Sized>(input: &mut Vec<char>, rng: &mut R) {
     let value = choose_random_consonant(rng);
 
     input.push(*value);
 }
 
-fn create_closed_syllable(rng: &mut ThreadRng) -> Vec<char> {
+fn create_closed_syllable<R: Rng + Sized>(rng: &mut R) -> Vec<char> {
     vec![
         *choose_random_consonant(rng),
         *choose_random_vowel(rng),
@@ -43,7 +42,7 @@ fn create_closed_syllable(rng: &mut ThreadRng) -> Vec<char> {
     ]
 }
 
-fn create_open_syllable(rng: &mut ThreadRng) -> Vec<char> {
+fn create_open_syllable<R: Rng + Sized>(rng: &mut R) -> Vec<char> {
     vec![
         *choose_random_consonant(rng),
         *choose_random_vowel(rng)
@@ -51,15 +50,15 @@ fn create_open_syllable(rng: &mut ThreadRng) -> Vec<char> {
 }
 
 enum SyllableType {
-    CLOSED,
-    OPEN
+    Closed,
+    Open
 }
 
 impl Distribution<SyllableType> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> SyllableType {
         match rng.gen_range(0..=1) {
-            0 => SyllableType::CLOSED,
-            _ => SyllableType::OPEN
+            0 => SyllableType::Closed,
+            _ => SyllableType::Open
         }
     }
 }
@@ -68,11 +67,10 @@ impl Distribution<SyllableType> for Standard {
 ///
 /// Usernames created in this fashion are guaranteed to be pronouncable,
 /// but are likely to be flagged as suspicious by automated tools and may not be aesthetically pleasing.
-pub fn generate_simple_username(capitalize: bool, length: usize) -> Vec<u8> {
+pub fn generate_simple_username<R: Rng + Sized>(capitalize: bool, length: usize, rng: &mut R) -> Vec<u8> {
     if length == 0 { return Vec::<u8>::new(); }
 
     let mut output: Vec<char> = Vec::new();
-    let rng = &mut thread_rng();
     let start = rng.gen_bool(1.0 / 2.0);
 
     // Flip a coin to choose between starting with a vowel or a consonant.
@@ -104,17 +102,16 @@ pub fn generate_simple_username(capitalize: bool, length: usize) -> Vec<u8> {
 ///
 /// Syllabic usernames are less likely to be flagged as suspicious by automated tools,
 /// and may be more aesthetically pleasing.
-pub fn generate_complex_username(capitalize: bool, length: usize) -> Vec<u8> {
+pub fn generate_complex_username<R: Rng + Sized>(capitalize: bool, length: usize, rng: &mut R) -> Vec<u8> {
     if length == 0 { return Vec::<u8>::new(); }
 
-    let rng = &mut thread_rng();
     let mut output = Vec::<char>::new();
 
     for _ in 0..length {
         // Generate a random syllable of a random type.
-        let syllable = match random::<SyllableType>() {
-            SyllableType::CLOSED => create_closed_syllable(rng),
-            SyllableType::OPEN => create_open_syllable(rng)
+        let syllable = match rng.gen::<SyllableType>() {
+            SyllableType::Closed => create_closed_syllable(rng),
+            SyllableType::Open => create_open_syllable(rng)
         };
 
         output.extend(syllable);
@@ -127,15 +124,44 @@ pub fn generate_complex_username(capitalize: bool, length: usize) -> Vec<u8> {
         .into_bytes()
 }
 
+/// Calculate the entropy of a simple username of the given length, in bits.
+///
+/// The coin flip which chooses the first letter contributes one bit, and each letter contributes the entropy of
+/// a uniform choice of vowel or consonant.
+pub fn simple_username_entropy(length: usize) -> f64 {
+    if length == 0 { return 0.0; }
+
+    let vowel = (VOWELS.len() as f64).log2();
+    let consonant = (CONSONANTS.len() as f64).log2();
+    let (long, short) = (length.div_ceil(2) as f64, (length / 2) as f64);
+
+    1.0 + (long * vowel + short * consonant + long * consonant + short * vowel) / 2.0
+}
+
+/// Calculate the entropy of a complex username with the given number of syllables, in bits.
+///
+/// Every syllable contains exactly one vowel, so a username can only be split into syllables in one way
+/// and each syllable contributes its full entropy.
+pub fn complex_username_entropy(length: usize) -> f64 {
+    let vowel = (VOWELS.len() as f64).log2();
+    let consonant = (CONSONANTS.len() as f64).log2();
+    let closed = 2.0 * consonant + vowel;
+    let open = consonant + vowel;
+
+    length as f64 * (1.0 + (closed + open) / 2.0)
+}
+
 #[cfg(test)]
 mod tests {
     use std::str::from_utf8;
 
-    use super::{generate_simple_username, generate_complex_username};
+    use rand::thread_rng;
+
+    use super::{generate_simple_username, generate_complex_username, simple_username_entropy, complex_username_entropy};
 
     #[test]
     fn generates_ten_thousand_character_simple_username() {
-        let bytes = generate_simple_username(false, 10000);
+        let bytes = generate_simple_username(false, 10000, &mut thread_rng());
         let string = from_utf8(&bytes).unwrap();
 
         assert_eq!(string.chars().count(), 10000)
@@ -143,14 +169,14 @@ mod tests {
 
     #[test]
     fn generates_empty_simple_username() {
-        let bytes = generate_simple_username(false, 0);
+        let bytes = generate_simple_username(false, 0, &mut thread_rng());
 
         assert_eq!(bytes.len(), 0)
     }
 
     #[test]
     fn generates_ten_thousand_syllable_complex_username() {
-        let bytes = generate_complex_username(false, 10000);
+        let bytes = generate_complex_username(false, 10000, &mut thread_rng());
         let string = from_utf8(&bytes).unwrap();
         let length = string.chars().count();
         let range = 20000..=30000;
@@ -160,8 +186,15 @@ mod tests {
 
     #[test]
     fn generates_empty_complex_username() {
-        let bytes = generate_complex_username(false, 0);
+        let bytes = generate_complex_username(false, 0, &mut thread_rng());
 
         assert_eq!(bytes.len(), 0)
     }
+
+    #[test]
+    fn calculates_username_entropy() {
+        assert_eq!(simple_username_entropy(0), 0.0);
+        assert!((simple_username_entropy(2) - (1.0 + 120_f64.log2())).abs() < 1e-9);
+        assert!((complex_username_entropy(2) - (2.0 + 2400_f64.log2() + 120_f64.log2())).abs() < 1e-9);
+    }
 }
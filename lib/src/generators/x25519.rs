@@ -0,0 +1,114 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use bech32::{Bech32, Hrp};
+use rand::Rng;
+use x25519_dalek::{x25519, X25519_BASEPOINT_BYTES};
+
+use crate::generators::generate_bytes;
+
+/// The human-readable part of an age identity, which is written in uppercase.
+const AGE_SECRET_KEY_HRP: Hrp = Hrp::parse_unchecked("age-secret-key-");
+
+/// The human-readable part of an age recipient.
+const AGE_RECIPIENT_HRP: Hrp = Hrp::parse_unchecked("age");
+
+/// Generate a random X25519 private key, clamped as RFC 7748 requires.
+pub fn generate_x25519_secret<R: Rng + Sized>(rng: &mut R) -> [u8; 32] {
+    let mut secret = [0; 32];
+
+    secret.copy_from_slice(&generate_bytes(32, rng));
+
+    // Clear the three lowest bits so the key is a multiple of the cofactor, and fix the position of the highest bit.
+    secret[0] &= 248;
+    secret[31] &= 127;
+    secret[31] |= 64;
+
+    secret
+}
+
+/// Derive the X25519 public key of a private key.
+pub fn x25519_public_key(secret: &[u8; 32]) -> [u8; 32] {
+    x25519(*secret, X25519_BASEPOINT_BYTES)
+}
+
+/// Generate a WireGuard keypair as `wg genkey` and `wg pubkey` would, in the form used by a WireGuard configuration.
+///
+/// A preshared key is 32 random bytes which is shared by both peers, and isn't an X25519 key.
+pub fn generate_wireguard_keys<R: Rng + Sized>(preshared: bool, rng: &mut R) -> Vec<u8> {
+    let secret = generate_x25519_secret(rng);
+    let mut keys = format!(
+        "PrivateKey = {}\nPublicKey = {}",
+        STANDARD.encode(secret),
+        STANDARD.encode(x25519_public_key(&secret))
+    );
+
+    if preshared {
+        keys.push_str(&format!("\nPresharedKey = {}", STANDARD.encode(generate_bytes(32, rng))));
+    }
+
+    keys.into_bytes()
+}
+
+/// Generate an age identity in the form written by `age-keygen`, with its recipient in a comment above it.
+pub fn generate_age_identity<R: Rng + Sized>(rng: &mut R) -> Vec<u8> {
+    let secret = generate_x25519_secret(rng);
+
+    // Both strings are far shorter than the length limit of Bech32, so encoding can't fail.
+    let identity = bech32::encode_upper::<Bech32>(AGE_SECRET_KEY_HRP, &secret).unwrap();
+    let recipient = bech32::encode::<Bech32>(AGE_RECIPIENT_HRP, &x25519_public_key(&secret)).unwrap();
+
+    format!("# public key: {recipient}\n{identity}").into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::from_utf8;
+
+    use rand::thread_rng;
+
+    use super::*;
+
+    #[test]
+    fn derives_rfc7748_public_key() {
+        let mut secret = [0; 32];
+
+        secret.copy_from_slice(&hex::decode("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a").unwrap());
+
+        assert_eq!(hex::encode(x25519_public_key(&secret)), "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a");
+    }
+
+    #[test]
+    fn generates_wireguard_keys() {
+        let bytes = generate_wireguard_keys(true, &mut thread_rng());
+        let lines = from_utf8(&bytes).unwrap().lines().collect::<Vec<_>>();
+        let keys = lines.iter()
+            .map(|line| STANDARD.decode(line.split(" = ").nth(1).unwrap()).unwrap())
+            .collect::<Vec<_>>();
+        let mut secret = [0; 32];
+
+        secret.copy_from_slice(&keys[0]);
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[2].starts_with("PresharedKey = "));
+        assert_eq!(secret[0] & 7, 0);
+        assert_eq!(secret[31] & 192, 64);
+        assert_eq!(keys[1], x25519_public_key(&secret));
+        assert_eq!(keys[2].len(), 32);
+    }
+
+    #[test]
+    fn generates_age_identity() {
+        let bytes = generate_age_identity(&mut thread_rng());
+        let lines = from_utf8(&bytes).unwrap().lines().collect::<Vec<_>>();
+        let (recipient_hrp, recipient) = bech32::decode(lines[0].trim_start_matches("# public key: ")).unwrap();
+        let (identity_hrp, identity) = bech32::decode(lines[1]).unwrap();
+        let mut secret = [0; 32];
+
+        secret.copy_from_slice(&identity);
+
+        assert!(lines[1].starts_with("AGE-SECRET-KEY-1"));
+        assert_eq!(identity_hrp, AGE_SECRET_KEY_HRP);
+        assert_eq!(recipient_hrp, AGE_RECIPIENT_HRP);
+        assert_eq!(recipient, x25519_public_key(&secret));
+    }
+}
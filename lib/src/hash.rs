@@ -0,0 +1,340 @@
+use argon2::{Argon2, Params as Argon2Params};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use bcrypt::{hash_with_salt, Version};
+use clap::ValueEnum;
+use pbkdf2::{Algorithm as Pbkdf2Algorithm, Params as Pbkdf2Params, Pbkdf2};
+use rand::Rng;
+use scrypt::{Params as ScryptParams, Scrypt};
+use sha2::{Digest, Sha512};
+
+use crate::generators::generate_bytes;
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type StringResult<'a> = Result<String, BoxedError<'a>>;
+type BoolResult<'a> = Result<bool, BoxedError<'a>>;
+
+/// The number of random bytes in each salt.
+const SALT_LENGTH: usize = 16;
+
+/// The alphabet used by crypt(3) to encode salts and digests.
+const CRYPT_ALPHABET: &[u8; 64] = b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// The number of rounds sha512-crypt uses when none are given, which is then left out of the hash.
+const SHA512_CRYPT_DEFAULT_ROUNDS: u32 = 5000;
+
+/// The longest salt sha512-crypt will use, in characters.
+const SHA512_CRYPT_SALT_LENGTH: usize = 16;
+
+/// The order in which sha512-crypt encodes the bytes of its digest, three at a time.
+const SHA512_CRYPT_PERMUTATION: [(usize, usize, usize); 21] = [
+    (0, 21, 42), (22, 43, 1), (44, 2, 23), (3, 24, 45), (25, 46, 4), (47, 5, 26), (6, 27, 48),
+    (28, 49, 7), (50, 8, 29), (9, 30, 51), (31, 52, 10), (53, 11, 32), (12, 33, 54), (34, 55, 13),
+    (56, 14, 35), (15, 36, 57), (37, 58, 16), (59, 17, 38), (18, 39, 60), (40, 61, 19), (62, 20, 41)
+];
+
+/// The password hashing schemes which can be used to store a password.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum HashAlgorithm {
+    Argon2id,
+    Scrypt,
+    #[value(name = "pbkdf2-sha256")]
+    Pbkdf2Sha256,
+    #[value(name = "pbkdf2-sha512")]
+    Pbkdf2Sha512,
+    Bcrypt,
+    #[value(name = "sha512-crypt")]
+    Sha512Crypt
+}
+
+/// The cost parameters of a password hash. Any which are left empty use the current recommendation for the scheme.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct HashParameters {
+    /// The memory used by Argon2id, in kibibytes.
+    pub memory: Option<u32>,
+    /// The number of passes made by Argon2id.
+    pub time: Option<u32>,
+    /// The number of lanes used by Argon2id, or the parallelization parameter of scrypt.
+    pub parallelism: Option<u32>,
+    /// The base two logarithm of the scrypt cost parameter N.
+    pub log_n: Option<u8>,
+    /// The block size parameter r of scrypt.
+    pub block_size: Option<u32>,
+    /// The number of iterations of PBKDF2, or the number of rounds of sha512-crypt.
+    pub iterations: Option<u32>,
+    /// The cost factor of bcrypt.
+    pub cost: Option<u32>
+}
+
+/// Encodes three bytes as [count] characters of the crypt(3) alphabet, starting with the least significant bits.
+fn crypt_encode(output: &mut String, bytes: (u8, u8, u8), count: usize) {
+    let mut value = ((bytes.0 as u32) << 16) | ((bytes.1 as u32) << 8) | bytes.2 as u32;
+
+    for _ in 0..count {
+        output.push(CRYPT_ALPHABET[(value & 0x3f) as usize] as char);
+        value >>= 6;
+    }
+}
+
+/// Repeats [digest] until it is [length] bytes long.
+fn repeat_digest(digest: &[u8], length: usize) -> Vec<u8> {
+    digest.iter().copied().cycle().take(length).collect()
+}
+
+/// Computes a sha512-crypt hash as specified by Ulrich Drepper, which is the `$6$` scheme of glibc.
+///
+/// The number of rounds is only written to the hash when [explicit] is set or it differs from the default.
+fn sha512_crypt(password: &[u8], salt: &[u8], rounds: u32, explicit: bool) -> String {
+    let salt = &salt[..salt.len().min(SHA512_CRYPT_SALT_LENGTH)];
+    let alternate = Sha512::new()
+        .chain_update(password)
+        .chain_update(salt)
+        .chain_update(password)
+        .finalize();
+
+    let mut hasher = Sha512::new()
+        .chain_update(password)
+        .chain_update(salt)
+        .chain_update(repeat_digest(&alternate, password.len()));
+
+    // Each bit of the length of the password, from least significant, adds either the alternate digest or the password.
+    let mut length = password.len();
+
+    while length > 0 {
+        if length & 1 != 0 { hasher.update(alternate); }
+        else { hasher.update(password); }
+
+        length >>= 1;
+    }
+
+    let initial = hasher.finalize();
+    let password_digest = password.iter().fold(Sha512::new(), |hasher, _| hasher.chain_update(password)).finalize();
+    let salt_digest = (0..16 + initial[0] as usize).fold(Sha512::new(), |hasher, _| hasher.chain_update(salt)).finalize();
+    let password_sequence = repeat_digest(&password_digest, password.len());
+    let salt_sequence = repeat_digest(&salt_digest, salt.len());
+    let mut digest = initial;
+
+    for round in 0..rounds {
+        let mut hasher = Sha512::new();
+
+        if round % 2 != 0 { hasher.update(&password_sequence); }
+        else { hasher.update(digest); }
+
+        if round % 3 != 0 { hasher.update(&salt_sequence); }
+        if round % 7 != 0 { hasher.update(&password_sequence); }
+
+        if round % 2 != 0 { hasher.update(digest); }
+        else { hasher.update(&password_sequence); }
+
+        digest = hasher.finalize();
+    }
+
+    let mut output = String::from("$6$");
+
+    if explicit || rounds != SHA512_CRYPT_DEFAULT_ROUNDS { output.push_str(&format!("rounds={rounds}$")); }
+
+    output.push_str(&String::from_utf8_lossy(salt));
+    output.push('$');
+
+    for (first, second, third) in SHA512_CRYPT_PERMUTATION {
+        crypt_encode(&mut output, (digest[first], digest[second], digest[third]), 4);
+    }
+
+    crypt_encode(&mut output, (0, 0, digest[63]), 2);
+
+    output
+}
+
+/// Checks a password against a sha512-crypt hash of the form `$6$[rounds=N$]salt$digest`.
+fn verify_sha512_crypt<'a>(password: &[u8], hash: &str) -> BoolResult<'a> {
+    let fields = hash.trim_start_matches("$6$").split('$').collect::<Vec<_>>();
+    let (rounds, salt) = match fields.as_slice() {
+        [rounds, salt, _] if rounds.starts_with("rounds=") => (rounds["rounds=".len()..].parse::<u32>()?, salt),
+        [salt, _] => (SHA512_CRYPT_DEFAULT_ROUNDS, salt),
+        _ => return Err("The sha512-crypt hash is malformed".into())
+    };
+
+    let explicit = fields.len() == 3;
+    let expected = sha512_crypt(password, salt.as_bytes(), rounds.clamp(1000, 999_999_999), explicit);
+
+    // Compare every byte so the time taken doesn't reveal how much of the hash matched.
+    let matches = expected.len() == hash.len() && expected.bytes()
+        .zip(hash.bytes())
+        .fold(0, |difference, (left, right)| difference | (left ^ right)) == 0;
+
+    Ok(matches)
+}
+
+/// Hash a password for storage, using a salt drawn from the random number generator.
+///
+/// Argon2id, scrypt and PBKDF2 produce PHC strings, bcrypt produces `$2b$` hashes and sha512-crypt produces
+/// the `$6$` hashes found in `/etc/shadow`.
+pub fn hash_password<'a, R: Rng + Sized>(password: &[u8], algorithm: HashAlgorithm, parameters: &HashParameters, rng: &mut R) -> StringResult<'a> {
+    let salt = generate_bytes(SALT_LENGTH, rng);
+    let phc_salt = SaltString::encode_b64(&salt).map_err(|error| error.to_string())?;
+
+    let hash = match algorithm {
+        HashAlgorithm::Argon2id => {
+            let params = Argon2Params::new(
+                parameters.memory.unwrap_or(19456),
+                parameters.time.unwrap_or(2),
+                parameters.parallelism.unwrap_or(1),
+                None
+            ).map_err(|error| error.to_string())?;
+
+            Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                .hash_password(password, &phc_salt)
+                .map_err(|error| error.to_string())?
+                .to_string()
+        },
+        HashAlgorithm::Scrypt => {
+            let params = ScryptParams::new(
+                parameters.log_n.unwrap_or(17),
+                parameters.block_size.unwrap_or(8),
+                parameters.parallelism.unwrap_or(1),
+                32
+            ).map_err(|error| error.to_string())?;
+
+            Scrypt.hash_password_customized(password, None, None, params, &phc_salt)
+                .map_err(|error| error.to_string())?
+                .to_string()
+        },
+        HashAlgorithm::Pbkdf2Sha256 | HashAlgorithm::Pbkdf2Sha512 => {
+            let (identifier, rounds, output_length) = match algorithm {
+                HashAlgorithm::Pbkdf2Sha256 => (Pbkdf2Algorithm::Pbkdf2Sha256, 600_000, 32),
+                _ => (Pbkdf2Algorithm::Pbkdf2Sha512, 210_000, 64)
+            };
+            let params = Pbkdf2Params { rounds: parameters.iterations.unwrap_or(rounds), output_length };
+
+            Pbkdf2.hash_password_customized(password, Some(identifier.ident()), None, params, &phc_salt)
+                .map_err(|error| error.to_string())?
+                .to_string()
+        },
+        HashAlgorithm::Bcrypt => {
+            let mut bcrypt_salt = [0; SALT_LENGTH];
+
+            bcrypt_salt.copy_from_slice(&salt);
+
+            hash_with_salt(password, parameters.cost.unwrap_or(12), bcrypt_salt)?.format_for_version(Version::TwoB)
+        },
+        HashAlgorithm::Sha512Crypt => {
+            let rounds = parameters.iterations.unwrap_or(SHA512_CRYPT_DEFAULT_ROUNDS);
+
+            if !(1000..=999_999_999).contains(&rounds) {
+                return Err("sha512-crypt requires between 1000 and 999999999 rounds".into());
+            }
+
+            // The salt is written in the crypt(3) alphabet, and twelve bytes fill the sixteen characters it may use.
+            let mut encoded = String::new();
+
+            for bytes in salt[..12].chunks(3) {
+                crypt_encode(&mut encoded, (bytes[0], bytes[1], bytes[2]), 4);
+            }
+
+            sha512_crypt(password, encoded.as_bytes(), rounds, false)
+        }
+    };
+
+    Ok(hash)
+}
+
+/// Check whether a password matches a hash produced by any of the supported schemes.
+pub fn verify_password<'a>(password: &[u8], hash: &str) -> BoolResult<'a> {
+    let hash = hash.trim();
+
+    if hash.starts_with("$2a$") || hash.starts_with("$2b$") || hash.starts_with("$2x$") || hash.starts_with("$2y$") {
+        return Ok(bcrypt::verify(password, hash)?);
+    }
+
+    if hash.starts_with("$6$") { return verify_sha512_crypt(password, hash); }
+
+    let parsed = PasswordHash::new(hash).map_err(|error| format!("The hash is not in a recognised format: {error}"))?;
+    let result = match parsed.algorithm.as_str() {
+        "argon2id" | "argon2i" | "argon2d" => Argon2::default().verify_password(password, &parsed),
+        "scrypt" => Scrypt.verify_password(password, &parsed),
+        "pbkdf2-sha256" | "pbkdf2-sha512" => Pbkdf2.verify_password(password, &parsed),
+        algorithm => return Err(format!("The {algorithm} algorithm is not supported").into())
+    };
+
+    match result {
+        Ok(()) => Ok(true),
+        Err(argon2::password_hash::Error::Password) => Ok(false),
+        Err(error) => Err(error.to_string().into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use super::*;
+
+    // Test vectors from the sha512-crypt specification.
+    const SHA512_CRYPT_VECTORS: [(&str, &str, u32, bool, &str); 3] = [
+        (
+            "Hello world!",
+            "saltstring",
+            5000,
+            false,
+            "$6$saltstring$svn8UoSVapNtMuq1ukKS4tPQd8iKwSMHWjl/O817G3uBnIFNjnQJuesI68u4OTLiBFdcbYEdFCoEOfaS35inz1"
+        ),
+        (
+            "Hello world!",
+            "saltstringsaltstring",
+            10000,
+            true,
+            "$6$rounds=10000$saltstringsaltst$OW1/O6BYHV6BcXZu8QVeXbDWra3Oeqh0sbHbbMCVNSnCM/UrjmM0Dp8vOuZeHBy/YTBmSK6H9qs/y3RnOaw5v."
+        ),
+        (
+            "This is just a test",
+            "toolongsaltstring",
+            5000,
+            true,
+            "$6$rounds=5000$toolongsaltstrin$lQ8jolhgVRVhY4b5pZKaysCLi0QBxGoNeKQzQ3glMhwllF7oGDZxUhx1yxdYcz/e1JSbq3y6JMxxl8audkUEm0"
+        )
+    ];
+
+    #[test]
+    fn computes_sha512_crypt_vectors() {
+        for (password, salt, rounds, explicit, hash) in SHA512_CRYPT_VECTORS {
+            assert_eq!(sha512_crypt(password.as_bytes(), salt.as_bytes(), rounds, explicit), hash);
+            assert!(verify_password(password.as_bytes(), hash).unwrap());
+            assert!(!verify_password(b"wrong", hash).unwrap());
+        }
+    }
+
+    #[test]
+    fn hashes_and_verifies_every_algorithm() {
+        // The smallest costs each scheme accepts, so the test runs quickly.
+        let parameters = HashParameters {
+            memory: Some(8),
+            time: Some(1),
+            parallelism: Some(1),
+            log_n: Some(1),
+            block_size: Some(1),
+            iterations: Some(1000),
+            cost: Some(4)
+        };
+        let expected = [
+            (HashAlgorithm::Argon2id, "$argon2id$v=19$m=8,t=1,p=1$"),
+            (HashAlgorithm::Scrypt, "$scrypt$ln=1,r=1,p=1$"),
+            (HashAlgorithm::Pbkdf2Sha256, "$pbkdf2-sha256$i=1000,l=32$"),
+            (HashAlgorithm::Pbkdf2Sha512, "$pbkdf2-sha512$i=1000,l=64$"),
+            (HashAlgorithm::Bcrypt, "$2b$04$"),
+            (HashAlgorithm::Sha512Crypt, "$6$rounds=1000$")
+        ];
+
+        for (algorithm, prefix) in expected {
+            let hash = hash_password(b"correct horse", algorithm, &parameters, &mut thread_rng()).unwrap();
+
+            assert!(hash.starts_with(prefix), "{hash} does not start with {prefix}");
+            assert!(verify_password(b"correct horse", &hash).unwrap());
+            assert!(!verify_password(b"battery staple", &hash).unwrap());
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_hashes() {
+        assert!(verify_password(b"password", "5f4dcc3b5aa765d61d8327deb882cf99").is_err());
+        assert!(verify_password(b"password", "$6$").is_err());
+    }
+}
@@ -17,6 +17,15 @@ pub mod load;
 pub mod analyze;
 pub mod visualize;
 pub mod time;
+pub mod random;
+pub mod entropy;
+pub mod hash;
+pub mod encrypt;
+pub mod shamir;
+pub mod mac;
+pub mod encoding;
+pub mod literal;
+pub mod speech;
 
 pub use generators::*;
 pub use markov::*;
@@ -26,3 +35,12 @@ pub use load::*;
 pub use analyze::*;
 pub use visualize::*;
 pub use time::*;
+pub use random::*;
+pub use entropy::*;
+pub use hash::*;
+pub use encrypt::*;
+pub use shamir::*;
+pub use mac::*;
+pub use encoding::*;
+pub use literal::*;
+pub use speech::*;
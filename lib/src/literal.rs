@@ -0,0 +1,122 @@
+use clap::ValueEnum;
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type StringResult<'a> = Result<String, BoxedError<'a>>;
+
+/// The number of bytes on each line of a literal when no width is given, which is what `xxd -i` uses.
+pub const DEFAULT_LITERAL_WIDTH: usize = 12;
+
+/// The languages in which bytes can be written as an array literal.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Language {
+    C,
+    Rust,
+    Go,
+    Python,
+    Java,
+    Json
+}
+
+/// How an array literal is named and wrapped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiteralOptions {
+    /// The identifier of the array, or a conventional name for the language when none is given.
+    pub name: Option<String>,
+    /// The number of bytes on each line, or zero to write every byte on one line.
+    pub width: usize
+}
+
+impl Default for LiteralOptions {
+    fn default() -> Self {
+        LiteralOptions { name: None, width: DEFAULT_LITERAL_WIDTH }
+    }
+}
+
+impl Language {
+    /// The name of an array when none is given, following the convention for constants in the language.
+    fn default_name(&self) -> &'static str {
+        match self {
+            Language::Rust | Language::Python | Language::Java => "KEY",
+            _ => "key"
+        }
+    }
+
+    fn byte(&self, byte: u8) -> String {
+        match self {
+            Language::Json => byte.to_string(),
+            // Bytes are signed in Java, so those above 127 need a cast.
+            Language::Java if byte > 0x7f => format!("(byte) 0x{byte:02x}"),
+            _ => format!("0x{byte:02x}")
+        }
+    }
+}
+
+/// Write bytes as a typed array literal which can be pasted into source code.
+pub fn format_literal<'a>(bytes: &[u8], language: Language, options: &LiteralOptions) -> StringResult<'a> {
+    let name = options.name.as_deref().unwrap_or(language.default_name());
+    let valid = name.chars().next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && name.chars().all(|character| character.is_ascii_alphanumeric() || character == '_');
+
+    if !valid { return Err(format!("{name} isn't a valid identifier").into()); }
+
+    let length = bytes.len();
+    let width = if options.width == 0 { length.max(1) } else { options.width };
+    let named_json = language == Language::Json && options.name.is_some();
+    let (indent, separator, closing) = match language {
+        Language::Go => ("\t", ",", ""),
+        Language::Json if named_json => ("    ", "", "  "),
+        Language::Json => ("  ", "", ""),
+        _ => ("    ", ",", "")
+    };
+    let lines = bytes.chunks(width)
+        .map(|chunk| format!("{indent}{}", chunk.iter().map(|&byte| language.byte(byte)).collect::<Vec<_>>().join(", ")))
+        .collect::<Vec<_>>();
+
+    // Every language except JSON allows a comma after the last element, and Go requires one before a new line.
+    let body = match lines.is_empty() {
+        true => String::new(),
+        false => format!("\n{}{separator}\n{closing}", lines.join(",\n"))
+    };
+
+    let literal = match language {
+        Language::C => format!("static const uint8_t {name}[{length}] = {{{body}}};"),
+        Language::Rust => format!("const {name}: [u8; {length}] = [{body}];"),
+        Language::Go => format!("var {name} = [{length}]byte{{{body}}}"),
+        Language::Python => format!("{name} = bytes([{body}])"),
+        Language::Java => format!("static final byte[] {name} = {{{body}}};"),
+        Language::Json if named_json => format!("{{\n  \"{name}\": [{body}]\n}}"),
+        Language::Json => format!("[{body}]")
+    };
+
+    Ok(literal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_literals() {
+        let bytes = [0x00, 0x7f, 0x80, 0xff];
+        let options = LiteralOptions { name: None, width: 2 };
+
+        assert_eq!(format_literal(&bytes, Language::C, &options).unwrap(), "static const uint8_t key[4] = {\n    0x00, 0x7f,\n    0x80, 0xff,\n};");
+        assert_eq!(format_literal(&bytes, Language::Rust, &options).unwrap(), "const KEY: [u8; 4] = [\n    0x00, 0x7f,\n    0x80, 0xff,\n];");
+        assert_eq!(format_literal(&bytes, Language::Go, &options).unwrap(), "var key = [4]byte{\n\t0x00, 0x7f,\n\t0x80, 0xff,\n}");
+        assert_eq!(format_literal(&bytes, Language::Python, &options).unwrap(), "KEY = bytes([\n    0x00, 0x7f,\n    0x80, 0xff,\n])");
+        assert_eq!(
+            format_literal(&bytes, Language::Java, &options).unwrap(),
+            "static final byte[] KEY = {\n    0x00, 0x7f,\n    (byte) 0x80, (byte) 0xff,\n};"
+        );
+        assert_eq!(format_literal(&bytes, Language::Json, &options).unwrap(), "[\n  0, 127,\n  128, 255\n]");
+    }
+
+    #[test]
+    fn names_and_wraps_literals() {
+        let named = LiteralOptions { name: Some("seed".to_string()), width: 0 };
+
+        assert_eq!(format_literal(&[1, 2, 3], Language::Rust, &named).unwrap(), "const seed: [u8; 3] = [\n    0x01, 0x02, 0x03,\n];");
+        assert_eq!(format_literal(&[1, 2], Language::Json, &named).unwrap(), "{\n  \"seed\": [\n    1, 2\n  ]\n}");
+        assert!(format_literal(&[1], Language::C, &LiteralOptions { name: Some("2fa key".to_string()), width: 12 }).is_err());
+    }
+}
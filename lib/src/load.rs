@@ -5,12 +5,12 @@ use std::path::PathBuf;
 use bincode::{deserialize, serialize};
 use directories::ProjectDirs;
 use log::{debug, trace, warn};
-use rand::Rng;
-use rand::{thread_rng, prelude::SliceRandom};
+use rand::{Rng, prelude::SliceRandom};
 use zstd::{Decoder, Encoder};
 
+use crate::generators::PasswordPolicy;
 use crate::markov::{Generator, MarkovGenerator};
-use crate::wordlist::{load_default_wordlist, load_wordlist};
+use crate::wordlist::{load_default_wordlist, load_named_wordlist, load_wordlist};
 use crate::corpus::{load_corpus, load_default_corpus};
 
 type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
@@ -42,29 +42,21 @@ pub fn read_in<'a>(path: Option<PathBuf>) -> VecByteResult<'a> {
     Ok(buffer)
 }
 
-/// Loads the correct character set based on the available symbols
-pub fn get_character_set(numbers: bool, symbols: bool) -> Vec<char> {
-    let flags = (numbers, symbols);
-
-    let mut character_set: Vec<char> = match flags {
-        (true, true) => "0123456789!@*-_.abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ".chars().collect(),
-        (true, false) => "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ".chars().collect(),
-        (false, true) => "!@*-_.abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ".chars().collect(),
-        (false, false) => "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ".chars().collect()
-    };
-
-    let rng = &mut thread_rng();
+/// Loads the character set allowed by a password policy
+pub fn get_character_set<R: Rng + Sized>(policy: &PasswordPolicy, rng: &mut R) -> Vec<char> {
+    let mut character_set = policy.character_set();
 
     character_set.shuffle(rng);
 
     character_set
 }
 
-/// Retrieves a wordlist
-pub fn get_wordlist<'a, R: Rng + Sized>(path: Option<PathBuf>, delimiter: Option<&str>, rng: &mut R) -> VecStringResult<'a> {
-    let wordlist = match path {
-        Some(path) => load_wordlist(&path, &delimiter.unwrap_or("\n"), rng)?,
-        None => load_default_wordlist(rng)?,
+/// Retrieves a wordlist from a file, or a built-in wordlist by name
+pub fn get_wordlist<'a, R: Rng + Sized>(path: Option<PathBuf>, name: Option<&str>, delimiter: Option<&str>, rng: &mut R) -> VecStringResult<'a> {
+    let wordlist = match (path, name) {
+        (Some(path), _) => load_wordlist(&path, delimiter.unwrap_or("\n"), rng)?,
+        (None, Some(name)) => load_named_wordlist(name, rng)?,
+        (None, None) => load_default_wordlist(rng)?,
     };
 
     Ok(wordlist)
@@ -0,0 +1,157 @@
+use std::io::Read;
+
+use clap::ValueEnum;
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use pbkdf2::pbkdf2_hmac;
+use sha2::{Sha256, Sha512};
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type BytesResult<'a> = Result<Vec<u8>, BoxedError<'a>>;
+type BoolResult<'a> = Result<bool, BoxedError<'a>>;
+
+/// The size of the buffer used to read the message of a MAC.
+const READ_BUFFER_SIZE: usize = 65536;
+
+/// The hash function underlying HMAC, HKDF and PBKDF2.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum MacAlgorithm {
+    #[default]
+    Sha256,
+    Sha512
+}
+
+impl MacAlgorithm {
+    /// The number of bytes in the output of the hash function.
+    pub fn output_size(&self) -> usize {
+        match self {
+            MacAlgorithm::Sha256 => 32,
+            MacAlgorithm::Sha512 => 64
+        }
+    }
+}
+
+fn update_from<'a, M: Mac>(mut mac: M, input: &mut impl Read) -> Result<M, BoxedError<'a>> {
+    let mut buffer = vec![0; READ_BUFFER_SIZE];
+
+    loop {
+        match input.read(&mut buffer)? {
+            0 => return Ok(mac),
+            length => mac.update(&buffer[..length])
+        }
+    }
+}
+
+/// Compute the HMAC of everything which can be read from the input.
+pub fn compute_mac<'a>(key: &[u8], input: &mut impl Read, algorithm: MacAlgorithm) -> BytesResult<'a> {
+    // HMAC accepts keys of any length, so creating one can't fail.
+    let tag = match algorithm {
+        MacAlgorithm::Sha256 => update_from(Hmac::<Sha256>::new_from_slice(key).unwrap(), input)?.finalize().into_bytes().to_vec(),
+        MacAlgorithm::Sha512 => update_from(Hmac::<Sha512>::new_from_slice(key).unwrap(), input)?.finalize().into_bytes().to_vec()
+    };
+
+    Ok(tag)
+}
+
+/// Check whether a tag is the HMAC of everything which can be read from the input.
+///
+/// The comparison takes the same time however much of the tag is correct, so it can't be used to guess a tag one
+/// byte at a time.
+pub fn verify_mac<'a>(key: &[u8], input: &mut impl Read, algorithm: MacAlgorithm, tag: &[u8]) -> BoolResult<'a> {
+    let valid = match algorithm {
+        MacAlgorithm::Sha256 => update_from(Hmac::<Sha256>::new_from_slice(key).unwrap(), input)?.verify_slice(tag).is_ok(),
+        MacAlgorithm::Sha512 => update_from(Hmac::<Sha512>::new_from_slice(key).unwrap(), input)?.verify_slice(tag).is_ok()
+    };
+
+    Ok(valid)
+}
+
+/// The extract step of HKDF, which concentrates the entropy of the input keying material into a pseudorandom key.
+///
+/// An empty salt is replaced by a string of zeros as long as the output of the hash function, as RFC 5869 requires.
+pub fn hkdf_extract(salt: &[u8], secret: &[u8], algorithm: MacAlgorithm) -> Vec<u8> {
+    match algorithm {
+        MacAlgorithm::Sha256 => Hkdf::<Sha256>::extract(Some(salt), secret).0.to_vec(),
+        MacAlgorithm::Sha512 => Hkdf::<Sha512>::extract(Some(salt), secret).0.to_vec()
+    }
+}
+
+/// The expand step of HKDF, which derives a key of the given length from a pseudorandom key and a label.
+///
+/// HKDF can produce at most 255 times the output size of the hash function.
+pub fn hkdf_expand<'a>(key: &[u8], info: &[u8], length: usize, algorithm: MacAlgorithm) -> BytesResult<'a> {
+    let mut output = vec![0; length];
+    let expanded = match algorithm {
+        MacAlgorithm::Sha256 => Hkdf::<Sha256>::from_prk(key).map(|hkdf| hkdf.expand(info, &mut output)),
+        MacAlgorithm::Sha512 => Hkdf::<Sha512>::from_prk(key).map(|hkdf| hkdf.expand(info, &mut output))
+    };
+
+    match expanded {
+        Ok(Ok(())) => Ok(output),
+        Ok(Err(_)) => Err(format!("HKDF can derive at most {} bytes with {algorithm:?}", 255 * algorithm.output_size()).into()),
+        Err(_) => Err(format!("A pseudorandom key must be at least {} bytes long", algorithm.output_size()).into())
+    }
+}
+
+/// Derive a key of the given length from a password with PBKDF2.
+pub fn pbkdf2<'a>(password: &[u8], salt: &[u8], iterations: u32, length: usize, algorithm: MacAlgorithm) -> BytesResult<'a> {
+    if iterations == 0 { return Err("PBKDF2 needs at least one iteration".into()); }
+
+    let mut output = vec![0; length];
+
+    match algorithm {
+        MacAlgorithm::Sha256 => pbkdf2_hmac::<Sha256>(password, salt, iterations, &mut output),
+        MacAlgorithm::Sha512 => pbkdf2_hmac::<Sha512>(password, salt, iterations, &mut output)
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_rfc4231_macs() {
+        let key = [0x0b; 20];
+        let tag = compute_mac(&key, &mut &b"Hi There"[..], MacAlgorithm::Sha256).unwrap();
+
+        assert_eq!(hex::encode(&tag), "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7");
+        assert_eq!(
+            hex::encode(compute_mac(b"Jefe", &mut &b"what do ya want for nothing?"[..], MacAlgorithm::Sha512).unwrap()),
+            "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737"
+        );
+        assert!(verify_mac(&key, &mut &b"Hi There"[..], MacAlgorithm::Sha256, &tag).unwrap());
+        assert!(!verify_mac(&key, &mut &b"Hi there"[..], MacAlgorithm::Sha256, &tag).unwrap());
+        assert!(!verify_mac(&key, &mut &b"Hi There"[..], MacAlgorithm::Sha256, &tag[..16]).unwrap());
+    }
+
+    #[test]
+    fn derives_rfc5869_keys() {
+        let secret = [0x0b; 22];
+        let salt = hex::decode("000102030405060708090a0b0c").unwrap();
+        let info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
+        let key = hkdf_extract(&salt, &secret, MacAlgorithm::Sha256);
+
+        assert_eq!(hex::encode(&key), "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5");
+        assert_eq!(
+            hex::encode(hkdf_expand(&key, &info, 42, MacAlgorithm::Sha256).unwrap()),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+        assert_eq!(
+            hex::encode(hkdf_expand(&hkdf_extract(&[], &secret, MacAlgorithm::Sha256), &[], 42, MacAlgorithm::Sha256).unwrap()),
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8"
+        );
+        assert!(hkdf_expand(&key, &info, 255 * 32 + 1, MacAlgorithm::Sha256).is_err());
+        assert!(hkdf_expand(&key[..16], &info, 32, MacAlgorithm::Sha256).is_err());
+    }
+
+    #[test]
+    fn derives_pbkdf2_keys() {
+        assert_eq!(
+            hex::encode(pbkdf2(b"password", b"salt", 4096, 32, MacAlgorithm::Sha256).unwrap()),
+            "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"
+        );
+        assert!(pbkdf2(b"password", b"salt", 0, 32, MacAlgorithm::Sha256).is_err());
+    }
+}
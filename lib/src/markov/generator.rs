@@ -14,6 +14,12 @@ pub trait MarkovGenerator {
     /// Generates a word.
     fn generate<R: Rng + Sized>(&self, rng: &mut R) -> String;
 
+    /// Generates a word, giving up as soon as it reaches `maximum` letters.
+    fn generate_within<R: Rng + Sized>(&self, maximum: usize, rng: &mut R) -> Option<String>;
+
+    /// Calculates the self-information of a generated word in bits, which is how surprising it is to the generator.
+    fn information(&self, word: &str) -> f64;
+
     /// Calculates the CID of all models in this generator.
     fn calculate_checksum(&self) -> Vec<u8>;
 }
@@ -46,7 +52,7 @@ impl Display for Generator {
 
 impl MarkovGenerator for Generator {
     fn new(data: &[String], order: usize, prior: f64, backoff: bool) -> Self {
-        let mut alphabet = data.iter().map(|value| value.chars()).flatten().collect::<Vec<_>>();
+        let mut alphabet = data.iter().flat_map(|value| value.chars()).collect::<Vec<_>>();
 
         alphabet.sort();
         alphabet.dedup();
@@ -85,6 +91,42 @@ impl MarkovGenerator for Generator {
         word
     }
 
+    fn generate_within<R: Rng + Sized>(&self, maximum: usize, rng: &mut R) -> Option<String> {
+        let mut word = "#".repeat(self.order);
+
+        loop {
+            if word.len() - self.order >= maximum { return None; }
+
+            match self.get_letter(&word, rng) {
+                Some(letter) if letter != "#" => word += &letter,
+                _ => break
+            };
+        };
+
+        Some(word)
+    }
+
+    fn information(&self, word: &str) -> f64 {
+        let word = "#".repeat(self.order) + word;
+
+        // Each letter was chosen by the first model which recognised its context, exactly as in `get_letter`.
+        word.char_indices()
+            .skip(self.order)
+            .map(|(index, letter)| {
+                let mut context = &word[index - self.order..index];
+
+                for model in self.models.iter() {
+                    match model.probability(context, letter) {
+                        Some(probability) => return -probability.log2(),
+                        None => context = &context[1..]
+                    }
+                }
+
+                0.0
+            })
+            .sum()
+    }
+
     fn calculate_checksum(&self) -> Vec<u8> {
         let mut digest = Sha256::new();
 
@@ -98,7 +140,7 @@ impl MarkovGenerator for Generator {
 
 impl Generator {
     /// Generates the next letter in a word.
-    fn get_letter<R: Rng + Sized>(&self, word: &String, rng: &mut R) -> Option<String> {
+    fn get_letter<R: Rng + Sized>(&self, word: &str, rng: &mut R) -> Option<String> {
         if word.is_empty() { panic!("Word is empty"); }
 
         let length = word.len();
@@ -14,42 +14,43 @@ pub trait MarkovModel {
     fn retrain(&mut self);
 
     /// Attempts to generate the next letter in the word given the `context` (the previous "order" letters).
-    fn generate<R: Rng + Sized>(&self, context: &String, rng: &mut R) -> Option<String>;
+    fn generate<R: Rng + Sized>(&self, context: &str, rng: &mut R) -> Option<String>;
+
+    /// Calculates the probability that `generate` picks `letter` after the `context`, if the context has been observed.
+    fn probability(&self, context: &str, letter: char) -> Option<f64>;
 
     /// Calculates the CID of this model.
     fn calculate_checksum(&self) -> Vec<u8>;
 }
 
-trait IntoBytes {
-    fn into_bytes(&self) -> Vec<u8>;
+trait ToBytes {
+    fn to_bytes(&self) -> Vec<u8>;
 }
 
-impl IntoBytes for Vec<String> {
-    fn into_bytes(&self) -> Vec<u8> {
+impl ToBytes for Vec<String> {
+    fn to_bytes(&self) -> Vec<u8> {
         self.iter()
-            .map(|value| value.to_owned().into_bytes())
-            .flatten()
+            .flat_map(|value| value.to_owned().into_bytes())
             .collect::<Vec<_>>()
     }
 }
 
-impl IntoBytes for Vec<char> {
-    fn into_bytes(&self) -> Vec<u8> {
+impl ToBytes for Vec<char> {
+    fn to_bytes(&self) -> Vec<u8> {
         self.iter()
             .collect::<String>()
             .into_bytes()
     }
 }
 
-impl IntoBytes for HashMap<String, Vec<String>> {
-    fn into_bytes(&self) -> Vec<u8> {
+impl ToBytes for HashMap<String, Vec<String>> {
+    fn to_bytes(&self) -> Vec<u8> {
         self.iter()
-            .map(|(key, value)| {
+            .flat_map(|(key, value)| {
                 let mut buffer = Vec::<u8>::new();
                 let mut key = key.to_owned().into_bytes();
                 let mut value = value.iter()
-                    .map(|value| value.to_owned().into_bytes())
-                    .flatten()
+                    .flat_map(|value| value.to_owned().into_bytes())
                     .collect::<Vec<_>>();
 
                 buffer.append(&mut key);
@@ -57,20 +58,18 @@ impl IntoBytes for HashMap<String, Vec<String>> {
 
                 buffer
             })
-            .flatten()
             .collect::<Vec<_>>()
     }
 }
 
-impl IntoBytes for HashMap<String, Vec<f64>> {
-    fn into_bytes(&self) -> Vec<u8> {
+impl ToBytes for HashMap<String, Vec<f64>> {
+    fn to_bytes(&self) -> Vec<u8> {
         self.iter()
-            .map(|(key, value)| {
+            .flat_map(|(key, value)| {
                 let mut buffer = Vec::<u8>::new();
                 let mut key = key.to_owned().into_bytes();
                 let mut value = value.iter()
-                    .map(|value| value.to_le_bytes())
-                    .flatten()
+                    .flat_map(|value| value.to_le_bytes())
                     .collect::<Vec<_>>();
 
                     buffer.append(&mut key);
@@ -78,7 +77,6 @@ impl IntoBytes for HashMap<String, Vec<f64>> {
 
                 buffer
             })
-            .flatten()
             .collect::<Vec<_>>()
     }
 }
@@ -138,7 +136,7 @@ impl MarkovModel for Model {
         self.build_chains();
     }
 
-    fn generate<R: Rng + Sized>(&self, context: &String, rng: &mut R) -> Option<String> {
+    fn generate<R: Rng + Sized>(&self, context: &str, rng: &mut R) -> Option<String> {
         match self.chains.get(context) {
             Some(chain) => {
                 let index = select_index(chain, rng);
@@ -150,14 +148,30 @@ impl MarkovModel for Model {
         }
     }
 
+    fn probability(&self, context: &str, letter: char) -> Option<f64> {
+        let chain = self.chains.get(context)?;
+        let total = chain.iter().sum::<f64>();
+        let index = self.alphabet.iter().position(|&value| value == letter);
+
+        let probability = match index {
+            // A chain without any weight always yields the first letter of the alphabet.
+            Some(0) if total == 0.0 => 1.0,
+            Some(_) if total == 0.0 => 0.0,
+            Some(index) => chain[index] / total,
+            None => 0.0
+        };
+
+        Some(probability)
+    }
+
     fn calculate_checksum(&self) -> Vec<u8> {
         let mut digest = Sha256::new();
-        let data = self.data.into_bytes();
+        let data = self.data.to_bytes();
         let order = self.order.to_le_bytes();
         let prior = self.prior.to_le_bytes();
-        let alphabet = self.alphabet.into_bytes();
-        let observations = self.observations.into_bytes();
-        let chains = self.chains.into_bytes();
+        let alphabet = self.alphabet.to_bytes();
+        let observations = self.observations.to_bytes();
+        let chains = self.chains.to_bytes();
 
         digest.update(data);
         digest.update(order);
@@ -237,8 +251,8 @@ fn select_index<R: Rng + Sized>(chain: &Vec<f64>, rng: &mut R) -> usize {
 
     let random = rng.gen::<f64>() * accumulator;
 
-    for index in 0..totals.len() {
-        if random < totals[index] {
+    for (index, total) in totals.iter().enumerate() {
+        if random < *total {
             return index;
         }
     }
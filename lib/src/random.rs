@@ -0,0 +1,86 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+/// The cryptographically secure pseudo-random number generator which drives every generator.
+pub type Csprng = ChaCha20Rng;
+
+/// The stream reserved for work which happens before a batch is generated, such as shuffling a wordlist.
+const SETUP_STREAM: u64 = u64::MAX;
+
+/// A source of randomness from which independent streams can be derived.
+///
+/// Each item in a batch draws from its own ChaCha20 stream, selected by the index of the item,
+/// so a seeded batch is reproducible regardless of the order in which the items are generated.
+pub struct RandomSource {
+    seed: [u8; 32]
+}
+
+impl RandomSource {
+    /// Creates a source from the given seed, or from the operating system if no seed is given.
+    pub fn new(seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => Csprng::seed_from_u64(seed),
+            None => Csprng::from_entropy()
+        };
+
+        RandomSource { seed: rng.get_seed() }
+    }
+
+    /// Creates a source from a seed drawn from another generator, so work split across threads stays reproducible.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        RandomSource { seed }
+    }
+
+    /// Derives the stream for the item at [index] in a batch.
+    pub fn stream(&self, index: usize) -> Csprng {
+        let mut rng = Csprng::from_seed(self.seed);
+
+        rng.set_stream(index as u64);
+
+        rng
+    }
+
+    /// Derives the stream used to prepare a batch.
+    pub fn setup(&self) -> Csprng {
+        let mut rng = Csprng::from_seed(self.seed);
+
+        rng.set_stream(SETUP_STREAM);
+
+        rng
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generators::{generate_bytes, generate_password};
+
+    use super::*;
+
+    #[test]
+    fn seeded_streams_are_reproducible() {
+        let first = RandomSource::new(Some(42));
+        let second = RandomSource::new(Some(42));
+
+        assert_eq!(generate_bytes(64, &mut first.stream(7)), generate_bytes(64, &mut second.stream(7)));
+    }
+
+    #[test]
+    fn streams_are_independent() {
+        let source = RandomSource::new(Some(42));
+
+        assert_ne!(generate_bytes(64, &mut source.stream(0)), generate_bytes(64, &mut source.stream(1)));
+        assert_ne!(generate_bytes(64, &mut source.stream(0)), generate_bytes(64, &mut source.setup()));
+    }
+
+    #[test]
+    fn different_seeds_differ() {
+        let character_set: Vec<char> = "abcdefghijklmnopqrstuvwxyz".chars().collect();
+        let first = RandomSource::new(Some(1));
+        let second = RandomSource::new(Some(2));
+
+        assert_ne!(
+            generate_password(&character_set, 32, &mut first.stream(0)),
+            generate_password(&character_set, 32, &mut second.stream(0))
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use clap::ValueEnum;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::generators::generate_bytes;
+use crate::wordlist::{decode_words, encode_words};
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type SharesResult<'a> = Result<Vec<Vec<u8>>, BoxedError<'a>>;
+type BytesResult<'a> = Result<Vec<u8>, BoxedError<'a>>;
+
+/// The number of random bytes which identify the shares of one secret, so shares of different secrets aren't mixed.
+const IDENTIFIER_SIZE: usize = 2;
+
+/// The number of bytes of the SHA-256 digest of a share which are appended to it.
+const CHECKSUM_SIZE: usize = 4;
+
+/// The number of bytes before the shared data: the identifier, the threshold and the index of the share.
+const HEADER_SIZE: usize = IDENTIFIER_SIZE + 2;
+
+/// How shares are written down.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum ShareEncoding {
+    #[default]
+    Hex,
+    Base64,
+    /// Words from the canonical wordlist, which are easier to write down by hand or read aloud.
+    Words
+}
+
+/// Multiplies two elements of GF(256), using the reducing polynomial of AES.
+fn gf_multiply(mut left: u8, mut right: u8) -> u8 {
+    let mut product = 0;
+
+    while right != 0 {
+        if right & 1 != 0 { product ^= left; }
+
+        left = (left << 1) ^ if left & 0x80 != 0 { 0x1b } else { 0 };
+        right >>= 1;
+    }
+
+    product
+}
+
+/// Finds the multiplicative inverse of a non-zero element of GF(256), which is the element raised to the power 254.
+fn gf_inverse(value: u8) -> u8 {
+    (0..7).fold((1, gf_multiply(value, value)), |(result, square), _| (gf_multiply(result, square), gf_multiply(square, square)))
+        .0
+}
+
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    Sha256::digest(bytes)[..CHECKSUM_SIZE].try_into().unwrap()
+}
+
+/// Split a secret into [count] shares, any [threshold] of which can be combined to recover it.
+///
+/// Each byte of the secret is the constant term of its own random polynomial of degree `threshold - 1` over
+/// GF(256), and each share holds the value of every polynomial at a different point. A share begins with an
+/// identifier common to all of the shares, the threshold and its index, and ends with a checksum.
+pub fn split_secret<'a, R: Rng + Sized>(secret: &[u8], threshold: usize, count: usize, rng: &mut R) -> SharesResult<'a> {
+    if secret.is_empty() { return Err("The secret is empty".into()); }
+    if threshold < 2 { return Err("At least two shares must be required to recover the secret".into()); }
+    if threshold > count { return Err(format!("The threshold of {threshold} shares is more than the {count} shares being created").into()); }
+    if count > 255 { return Err("At most 255 shares can be created".into()); }
+
+    let identifier = generate_bytes(IDENTIFIER_SIZE, rng);
+    let coefficients = secret.iter()
+        .map(|&byte| [vec![byte], generate_bytes(threshold - 1, rng)].concat())
+        .collect::<Vec<_>>();
+
+    let shares = (1..=count as u8)
+        .map(|index| {
+            let mut share = [identifier.as_slice(), &[threshold as u8, index]].concat();
+
+            // Evaluate each polynomial at the index of the share, using Horner's method.
+            share.extend(coefficients.iter().map(|polynomial| {
+                polynomial.iter().rev().fold(0, |value, &coefficient| gf_multiply(value, index) ^ coefficient)
+            }));
+
+            let checksum = checksum(&share);
+
+            share.extend_from_slice(&checksum);
+            share
+        })
+        .collect();
+
+    Ok(shares)
+}
+
+/// Recover a secret from shares created by [split_secret].
+///
+/// Every share is checked before any are combined, so a mistyped share, a share of a different secret, a repeated
+/// share or too few shares are all reported rather than producing the wrong secret.
+pub fn combine_shares<'a>(shares: &[Vec<u8>]) -> BytesResult<'a> {
+    let first = match shares.first() {
+        Some(share) => share,
+        None => return Err("No shares were given".into())
+    };
+
+    for (position, share) in shares.iter().enumerate() {
+        if share.len() <= HEADER_SIZE + CHECKSUM_SIZE {
+            return Err(format!("Share {} is too short", position + 1).into());
+        }
+
+        let (body, expected) = share.split_at(share.len() - CHECKSUM_SIZE);
+
+        if checksum(body) != expected { return Err(format!("Share {} has an invalid checksum", position + 1).into()); }
+        if share[..=IDENTIFIER_SIZE] != first[..=IDENTIFIER_SIZE] || share.len() != first.len() {
+            return Err(format!("Share {} belongs to a different secret than share 1", position + 1).into());
+        }
+        if share[IDENTIFIER_SIZE + 1] == 0 { return Err(format!("Share {} has an invalid index", position + 1).into()); }
+        if shares[..position].iter().any(|other| other[IDENTIFIER_SIZE + 1] == share[IDENTIFIER_SIZE + 1]) {
+            return Err(format!("Share {} was given more than once", share[IDENTIFIER_SIZE + 1]).into());
+        }
+    }
+
+    let threshold = first[IDENTIFIER_SIZE] as usize;
+
+    if shares.len() < threshold {
+        return Err(format!("{threshold} shares are needed to recover the secret, but only {} were given", shares.len()).into());
+    }
+
+    let shares = &shares[..threshold];
+    let points = shares.iter().map(|share| share[IDENTIFIER_SIZE + 1]).collect::<Vec<_>>();
+
+    // The Lagrange basis polynomial of each share, evaluated at zero.
+    let weights = points.iter()
+        .map(|&point| {
+            points.iter()
+                .filter(|&&other| other != point)
+                .fold(1, |weight, &other| gf_multiply(weight, gf_multiply(other, gf_inverse(other ^ point))))
+        })
+        .collect::<Vec<_>>();
+
+    let secret = (HEADER_SIZE..first.len() - CHECKSUM_SIZE)
+        .map(|offset| {
+            shares.iter()
+                .zip(&weights)
+                .fold(0, |byte, (share, &weight)| byte ^ gf_multiply(share[offset], weight))
+        })
+        .collect();
+
+    Ok(secret)
+}
+
+/// Write a share in the given encoding.
+pub fn encode_share(share: &[u8], encoding: ShareEncoding, wordlist: &[String]) -> String {
+    match encoding {
+        ShareEncoding::Hex => hex::encode(share),
+        ShareEncoding::Base64 => STANDARD.encode(share),
+        ShareEncoding::Words => encode_words(wordlist, share)
+    }
+}
+
+/// Read a share written in the given encoding.
+pub fn decode_share<'a>(text: &str, encoding: ShareEncoding, wordlist: &[String]) -> BytesResult<'a> {
+    let text = text.trim();
+
+    match encoding {
+        ShareEncoding::Hex => Ok(hex::decode(text)?),
+        ShareEncoding::Base64 => Ok(STANDARD.decode(text)?),
+        ShareEncoding::Words => decode_words(wordlist, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use crate::wordlist::load_canonical_wordlist;
+
+    use super::*;
+
+    #[test]
+    fn multiplies_in_gf256() {
+        assert_eq!(gf_multiply(0x53, 0xca), 0x01);
+        assert_eq!(gf_multiply(0x57, 0x83), 0xc1);
+        assert_eq!(gf_inverse(0x53), 0xca);
+
+        for value in 1..=255 {
+            assert_eq!(gf_multiply(value, gf_inverse(value)), 1);
+        }
+    }
+
+    #[test]
+    fn combines_any_threshold_of_shares() {
+        let secret = b"correct horse battery staple";
+        let shares = split_secret(secret, 3, 5, &mut thread_rng()).unwrap();
+
+        for first in 0..5 {
+            for second in first + 1..5 {
+                for third in second + 1..5 {
+                    let subset = [shares[third].clone(), shares[first].clone(), shares[second].clone()];
+
+                    assert_eq!(combine_shares(&subset).unwrap(), secret);
+                }
+            }
+        }
+
+        assert_eq!(combine_shares(&shares).unwrap(), secret);
+    }
+
+    #[test]
+    fn rejects_bad_shares() {
+        let shares = split_secret(b"secret", 2, 3, &mut thread_rng()).unwrap();
+        let others = split_secret(b"others", 2, 3, &mut thread_rng()).unwrap();
+        let mut corrupted = shares[1].clone();
+
+        corrupted[5] ^= 1;
+
+        assert!(combine_shares(&shares[..1]).is_err());
+        assert!(combine_shares(&[shares[0].clone(), corrupted]).is_err());
+        assert!(combine_shares(&[shares[0].clone(), shares[0].clone()]).is_err());
+        assert!(combine_shares(&[shares[0].clone(), others[1].clone()]).is_err());
+        assert!(split_secret(b"secret", 4, 3, &mut thread_rng()).is_err());
+        assert!(split_secret(b"secret", 1, 3, &mut thread_rng()).is_err());
+    }
+
+    #[test]
+    fn encodes_shares() {
+        let wordlist = load_canonical_wordlist().unwrap();
+        let shares = split_secret(b"secret", 2, 2, &mut thread_rng()).unwrap();
+
+        for encoding in [ShareEncoding::Hex, ShareEncoding::Base64, ShareEncoding::Words] {
+            let decoded = shares.iter()
+                .map(|share| decode_share(&encode_share(share, encoding, &wordlist), encoding, &wordlist).unwrap())
+                .collect::<Vec<_>>();
+
+            assert_eq!(combine_shares(&decoded).unwrap(), b"secret");
+        }
+    }
+}
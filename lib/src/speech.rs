@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+
+use clap::ValueEnum;
+
+use crate::wordlist::{decode_words, encode_words};
+
+type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
+type StringResult<'a> = Result<String, BoxedError<'a>>;
+type BytesResult<'a> = Result<Vec<u8>, BoxedError<'a>>;
+
+/// The ways in which output can be written to be read aloud.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum Speech {
+    /// The PGP word list, with a two-syllable word for each even byte and a three-syllable word for each odd byte
+    Pgp,
+    /// The NATO phonetic alphabet, with a word for each character
+    Nato,
+    /// Words from the default wordlist, where every three bytes become a pair of words, two bytes left over become a
+    /// pair whose value lies beyond any three bytes could take, and one byte left over becomes a single word
+    Words
+}
+
+/// The two-syllable words of the PGP word list, which are used for the bytes at even positions.
+const PGP_EVEN_WORDS: [&str; 256] = [
+    "aardvark", "absurd", "accrue", "acme", "adrift", "adult", "afflict", "ahead",
+    "aimless", "Algol", "allow", "alone", "ammo", "ancient", "apple", "artist",
+    "assume", "Athens", "atlas", "Aztec", "baboon", "backfield", "backward", "banjo",
+    "beaming", "bedlamp", "beehive", "beeswax", "befriend", "Belfast", "berserk", "billiard",
+    "bison", "blackjack", "blockade", "blowtorch", "bluebird", "bombast", "bookshelf", "brackish",
+    "breadline", "breakup", "brickyard", "briefcase", "Burbank", "button", "buzzard", "cement",
+    "chairlift", "chatter", "checkup", "chisel", "choking", "chopper", "Christmas", "clamshell",
+    "classic", "classroom", "cleanup", "clockwork", "cobra", "commence", "concert", "cowbell",
+    "crackdown", "cranky", "crowfoot", "crucial", "crumpled", "crusade", "cubic", "dashboard",
+    "deadbolt", "deckhand", "dogsled", "dragnet", "drainage", "dreadful", "drifter", "dropper",
+    "drumbeat", "drunken", "Dupont", "dwelling", "eating", "edict", "egghead", "eightball",
+    "endorse", "endow", "enlist", "erase", "escape", "exceed", "eyeglass", "eyetooth",
+    "facial", "fallout", "flagpole", "flatfoot", "flytrap", "fracture", "framework", "freedom",
+    "frighten", "gazelle", "Geiger", "glitter", "glucose", "goggles", "goldfish", "gremlin",
+    "guidance", "hamlet", "highchair", "hockey", "indoors", "indulge", "inverse", "involve",
+    "island", "jawbone", "keyboard", "kickoff", "kiwi", "klaxon", "locale", "lockup",
+    "merit", "minnow", "miser", "Mohawk", "mural", "music", "necklace", "Neptune",
+    "newborn", "nightbird", "Oakland", "obtuse", "offload", "optic", "orca", "payday",
+    "peachy", "pheasant", "physique", "playhouse", "Pluto", "preclude", "prefer", "preshrunk",
+    "printer", "prowler", "pupil", "puppy", "python", "quadrant", "quiver", "quota",
+    "ragtime", "ratchet", "rebirth", "reform", "regain", "reindeer", "rematch", "repay",
+    "retouch", "revenge", "reward", "rhythm", "ribcage", "ringbolt", "robust", "rocker",
+    "ruffled", "sailboat", "sawdust", "scallion", "scenic", "scorecard", "Scotland", "seabird",
+    "select", "sentence", "shadow", "shamrock", "showgirl", "skullcap", "skydive", "slingshot",
+    "slowdown", "snapline", "snapshot", "snowcap", "snowslide", "solo", "southward", "soybean",
+    "spaniel", "spearhead", "spellbind", "spheroid", "spigot", "spindle", "spyglass", "stagehand",
+    "stagnate", "stairway", "standard", "stapler", "steamship", "sterling", "stockman", "stopwatch",
+    "stormy", "sugar", "surmount", "suspense", "sweatband", "swelter", "tactics", "talon",
+    "tapeworm", "tempest", "tiger", "tissue", "tonic", "topmost", "tracker", "transit",
+    "trauma", "treadmill", "Trojan", "trouble", "tumor", "tunnel", "tycoon", "uncut",
+    "unearth", "unwind", "uproot", "upset", "upshot", "vapor", "village", "virus",
+    "Vulcan", "waffle", "wallet", "watchword", "wayside", "willow", "woodlark", "Zulu"
+];
+
+/// The three-syllable words of the PGP word list, which are used for the bytes at odd positions.
+const PGP_ODD_WORDS: [&str; 256] = [
+    "adroitness", "adviser", "aftermath", "aggregate", "alkali", "almighty", "amulet", "amusement",
+    "antenna", "applicant", "Apollo", "armistice", "article", "asteroid", "Atlantic", "atmosphere",
+    "autopsy", "Babylon", "backwater", "barbecue", "belowground", "bifocals", "bodyguard", "bookseller",
+    "borderline", "bottomless", "Bradbury", "bravado", "Brazilian", "breakaway", "Burlington", "businessman",
+    "butterfat", "Camelot", "candidate", "cannonball", "Capricorn", "caravan", "caretaker", "celebrate",
+    "cellulose", "certify", "chambermaid", "Cherokee", "Chicago", "clergyman", "coherence", "combustion",
+    "commando", "company", "component", "concurrent", "confidence", "conformist", "congregate", "consensus",
+    "consulting", "corporate", "corrosion", "councilman", "crossover", "crucifix", "cumbersome", "customer",
+    "Dakota", "decadence", "December", "decimal", "designing", "detector", "detergent", "determine",
+    "dictator", "dinosaur", "direction", "disable", "disbelief", "disruptive", "distortion", "document",
+    "embezzle", "enchanting", "enrollment", "enterprise", "equation", "equipment", "escapade", "Eskimo",
+    "everyday", "examine", "existence", "exodus", "fascinate", "filament", "finicky", "forever",
+    "fortitude", "frequency", "gadgetry", "Galveston", "getaway", "glossary", "gossamer", "graduate",
+    "gravity", "guitarist", "hamburger", "Hamilton", "handiwork", "hazardous", "headwaters", "hemisphere",
+    "hesitate", "hideaway", "holiness", "hurricane", "hydraulic", "impartial", "impetus", "inception",
+    "indigo", "inertia", "infancy", "inferno", "informant", "insincere", "insurgent", "integrate",
+    "intention", "inventive", "Istanbul", "Jamaica", "Jupiter", "leprosy", "letterhead", "liberty",
+    "maritime", "matchmaker", "maverick", "Medusa", "megaton", "microscope", "microwave", "midsummer",
+    "millionaire", "miracle", "misnomer", "molasses", "molecule", "Montana", "monument", "mosquito",
+    "narrative", "nebula", "newsletter", "Norwegian", "October", "Ohio", "onlooker", "opulent",
+    "Orlando", "outfielder", "Pacific", "pandemic", "Pandora", "paperweight", "paragon", "paragraph",
+    "paramount", "passenger", "pedigree", "Pegasus", "penetrate", "perceptive", "performance", "pharmacy",
+    "phonetic", "photograph", "pioneer", "pocketful", "politeness", "positive", "potato", "processor",
+    "provincial", "proximate", "puberty", "publisher", "pyramid", "quantity", "racketeer", "rebellion",
+    "recipe", "recover", "repellent", "replica", "reproduce", "resistor", "responsive", "retraction",
+    "retrieval", "retrospect", "revenue", "revival", "revolver", "sandalwood", "sardonic", "Saturday",
+    "savagery", "scavenger", "sensation", "sociable", "souvenir", "specialist", "speculate", "stethoscope",
+    "stupendous", "supportive", "surrender", "suspicious", "sympathy", "tambourine", "telephone", "therapist",
+    "tobacco", "tolerance", "tomorrow", "torpedo", "tradition", "travesty", "trombonist", "truncated",
+    "typewriter", "ultimate", "undaunted", "underfoot", "unicorn", "unify", "universe", "unravel",
+    "upcoming", "vacancy", "vagabond", "vertigo", "Virginia", "visitor", "vocalist", "voyager",
+    "warranty", "Waterloo", "whimsical", "Wichita", "Wilmington", "Wyoming", "yesteryear", "Yucatan"
+];
+
+/// The words of the NATO phonetic alphabet, in the order of the letters they stand for.
+const NATO_LETTERS: [&str; 26] = [
+    "Alfa", "Bravo", "Charlie", "Delta", "Echo", "Foxtrot", "Golf", "Hotel", "India", "Juliett", "Kilo", "Lima", "Mike",
+    "November", "Oscar", "Papa", "Quebec", "Romeo", "Sierra", "Tango", "Uniform", "Victor", "Whiskey", "Xray", "Yankee",
+    "Zulu"
+];
+
+/// The spoken names of the digits.
+const NATO_DIGITS: [&str; 10] = ["Zero", "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine"];
+
+/// The spoken names of the space and every ASCII symbol.
+const SYMBOL_NAMES: [(char, &str); 33] = [
+    (' ', "Space"), ('!', "Exclamation"), ('"', "Quote"), ('#', "Hash"), ('$', "Dollar"), ('%', "Percent"),
+    ('&', "Ampersand"), ('\'', "Apostrophe"), ('(', "Open-Paren"), (')', "Close-Paren"), ('*', "Asterisk"),
+    ('+', "Plus"), (',', "Comma"), ('-', "Dash"), ('.', "Period"), ('/', "Slash"), (':', "Colon"), (';', "Semicolon"),
+    ('<', "Less-Than"), ('=', "Equals"), ('>', "Greater-Than"), ('?', "Question"), ('@', "At"), ('[', "Open-Bracket"),
+    ('\\', "Backslash"), (']', "Close-Bracket"), ('^', "Caret"), ('_', "Underscore"), ('`', "Backtick"),
+    ('{', "Open-Brace"), ('|', "Pipe"), ('}', "Close-Brace"), ('~', "Tilde")
+];
+
+/// Encodes bytes as words from the PGP word list, alternating between the even and odd lists.
+///
+/// The words of the two lists differ in length, so a word which is skipped, repeated or swapped with its neighbour
+/// is noticed when the words are read back.
+pub fn encode_pgp_words(bytes: &[u8]) -> String {
+    bytes.iter()
+        .enumerate()
+        .map(|(position, &byte)| match position % 2 {
+            0 => PGP_EVEN_WORDS[byte as usize],
+            _ => PGP_ODD_WORDS[byte as usize]
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Decodes words produced by [encode_pgp_words] back into bytes, ignoring case.
+pub fn decode_pgp_words<'a>(text: &str) -> BytesResult<'a> {
+    let index = |words: [&'static str; 256]| words.iter()
+        .enumerate()
+        .map(|(byte, word)| (word.to_lowercase(), byte as u8))
+        .collect::<HashMap<_, _>>();
+    let lists = [index(PGP_EVEN_WORDS), index(PGP_ODD_WORDS)];
+
+    text.split_whitespace()
+        .enumerate()
+        .map(|(position, word)| {
+            let lowercase = word.to_lowercase();
+
+            match (lists[position % 2].get(&lowercase), lists[1 - position % 2].contains_key(&lowercase)) {
+                (Some(&byte), _) => Ok(byte),
+                (None, true) => Err(format!(
+                    "Word {} ({word}) belongs at an {} position, so a word was skipped, repeated or swapped",
+                    position + 1,
+                    ["even", "odd"][1 - position % 2]
+                ).into()),
+                (None, false) => Err(format!("Word {} ({word}) is not in the PGP word list", position + 1).into())
+            }
+        })
+        .collect()
+}
+
+/// Spells text with the NATO phonetic alphabet, naming digits and symbols and marking uppercase letters as capitals.
+pub fn spell_nato<'a>(text: &str) -> StringResult<'a> {
+    let words = text.chars()
+        .enumerate()
+        .map(|(position, character)| match character {
+            'a'..='z' => Ok(NATO_LETTERS[character as usize - 'a' as usize].to_string()),
+            'A'..='Z' => Ok(format!("Capital-{}", NATO_LETTERS[character as usize - 'A' as usize])),
+            '0'..='9' => Ok(NATO_DIGITS[character as usize - '0' as usize].to_string()),
+            _ => match SYMBOL_NAMES.iter().find(|(symbol, _)| *symbol == character) {
+                Some((_, name)) => Ok(name.to_string()),
+                None => Err(format!("The character {character:?} at position {} can't be spelled", position + 1))
+            }
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(words.join(" "))
+}
+
+/// Reads text spelled by [spell_nato] back into characters, ignoring case.
+pub fn read_nato<'a>(text: &str) -> StringResult<'a> {
+    let mut characters = HashMap::new();
+
+    for (offset, word) in NATO_LETTERS.iter().enumerate() {
+        characters.insert(word.to_lowercase(), (b'a' + offset as u8) as char);
+        characters.insert(format!("capital-{}", word.to_lowercase()), (b'A' + offset as u8) as char);
+    }
+
+    for (offset, word) in NATO_DIGITS.iter().enumerate() {
+        characters.insert(word.to_lowercase(), (b'0' + offset as u8) as char);
+    }
+
+    for (symbol, name) in SYMBOL_NAMES {
+        characters.insert(name.to_lowercase(), symbol);
+    }
+
+    text.split_whitespace()
+        .enumerate()
+        .map(|(position, word)| match characters.get(&word.to_lowercase()) {
+            Some(&character) => Ok(character),
+            None => Err(format!("Word {} ({word}) is not in the NATO alphabet", position + 1).into())
+        })
+        .collect()
+}
+
+/// Writes bytes to be read aloud, where the NATO alphabet spells their hexadecimal digits.
+///
+/// The wordlist is only used by [Speech::Words], and must be the canonical wordlist for the words to be read back.
+pub fn speak_bytes<'a>(bytes: &[u8], speech: Speech, wordlist: &[String]) -> StringResult<'a> {
+    match speech {
+        Speech::Pgp => Ok(encode_pgp_words(bytes)),
+        Speech::Nato => spell_nato(&hex::encode(bytes)),
+        Speech::Words => Ok(encode_words(wordlist, bytes))
+    }
+}
+
+/// Writes text to be read aloud, where the NATO alphabet spells its characters and the word lists encode its bytes.
+pub fn speak_text<'a>(text: &str, speech: Speech, wordlist: &[String]) -> StringResult<'a> {
+    match speech {
+        Speech::Nato => spell_nato(text),
+        _ => speak_bytes(text.as_bytes(), speech, wordlist)
+    }
+}
+
+/// Reads words written by [speak_bytes] or [speak_text] back into bytes.
+///
+/// Words from the NATO alphabet become the characters they spell, so spelled bytes come back as hexadecimal.
+pub fn read_speech<'a>(text: &str, speech: Speech, wordlist: &[String]) -> BytesResult<'a> {
+    match speech {
+        Speech::Pgp => decode_pgp_words(text),
+        Speech::Nato => Ok(read_nato(text)?.into_bytes()),
+        Speech::Words => decode_words(wordlist, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::wordlist::load_canonical_wordlist;
+
+    use super::*;
+
+    #[test]
+    fn encodes_the_pgp_word_list_vector() {
+        let bytes = hex::decode("E58294F2E9A227486E8B061B31CC528FD7FA3F19").unwrap();
+        let words = "topmost Istanbul Pluto vagabond treadmill Pacific brackish dictator goldfish Medusa \
+            afflict bravado chatter revolver Dupont midsummer stopwatch whimsical cowbell bottomless";
+
+        assert_eq!(encode_pgp_words(&bytes), words.split_whitespace().collect::<Vec<_>>().join(" "));
+        assert_eq!(decode_pgp_words(&words.to_uppercase()).unwrap(), bytes);
+    }
+
+    #[test]
+    fn notices_misplaced_pgp_words() {
+        assert!(decode_pgp_words("topmost Pluto").unwrap_err().to_string().contains("even"));
+        assert!(decode_pgp_words("Istanbul").unwrap_err().to_string().contains("odd"));
+        assert!(decode_pgp_words("topmost banana").unwrap_err().to_string().contains("not in"));
+
+        for list in [PGP_EVEN_WORDS, PGP_ODD_WORDS] {
+            assert_eq!(list.iter().map(|word| word.to_lowercase()).collect::<HashSet<_>>().len(), 256);
+        }
+    }
+
+    #[test]
+    fn spells_with_the_nato_alphabet() {
+        let spelled = spell_nato("aZ9!_ ~").unwrap();
+
+        assert_eq!(spelled, "Alfa Capital-Zulu Nine Exclamation Underscore Space Tilde");
+        assert_eq!(read_nato(&spelled.to_lowercase()).unwrap(), "aZ9!_ ~");
+        assert_eq!(speak_bytes(&[0xbe, 0x01], Speech::Nato, &[]).unwrap(), "Bravo Echo Zero One");
+        assert!(spell_nato("é").is_err());
+        assert!(read_nato("Alfa Bravo Chalie").is_err());
+    }
+
+    #[test]
+    fn reads_back_every_speech() {
+        let wordlist = load_canonical_wordlist().unwrap();
+
+        for speech in [Speech::Pgp, Speech::Nato, Speech::Words] {
+            let text = speak_text("Tr0ub4dor&3", speech, &wordlist).unwrap();
+
+            assert_eq!(read_speech(&text, speech, &wordlist).unwrap(), b"Tr0ub4dor&3");
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use clap::ValueEnum;
-use chrono::{Local, Utc};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, Utc};
 
 const DEFAULT_FORMAT: &str = "%F %T";
 
@@ -32,6 +32,36 @@ pub fn create_timestamp_local(format: Option<TimestampFormats>) -> Vec<u8> {
     timestamp.into_bytes()
 }
 
+/// Get the number of milliseconds elapsed since the Unix epoch.
+pub fn unix_timestamp_millis() -> u64 {
+    Utc::now().timestamp_millis() as u64
+}
+
+/// Get the number of seconds elapsed since the Unix epoch.
+pub fn unix_timestamp_secs() -> u64 {
+    unix_timestamp_millis() / 1000
+}
+
+/// Format a number of seconds since the Unix epoch as the `YYMMDDhhmmss` date of a Hashcash stamp, in UTC.
+pub fn format_hashcash_date(timestamp: u64) -> String {
+    DateTime::from_timestamp(timestamp as i64, 0).unwrap_or_default().format("%y%m%d%H%M%S").to_string()
+}
+
+/// Parse the date of a Hashcash stamp, which may be `YYMMDD`, `YYMMDDhhmm` or `YYMMDDhhmmss` in UTC, into a number
+/// of seconds since the Unix epoch.
+pub fn parse_hashcash_date(date: &str) -> Option<u64> {
+    if !date.bytes().all(|byte| byte.is_ascii_digit()) { return None; }
+
+    let parsed = match date.len() {
+        6 => NaiveDate::parse_from_str(date, "%y%m%d").ok()?.and_hms_opt(0, 0, 0)?,
+        10 => NaiveDateTime::parse_from_str(&format!("{date}00"), "%y%m%d%H%M%S").ok()?,
+        12 => NaiveDateTime::parse_from_str(date, "%y%m%d%H%M%S").ok()?,
+        _ => return None
+    };
+
+    u64::try_from(parsed.and_utc().timestamp()).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
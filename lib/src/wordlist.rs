@@ -1,11 +1,16 @@
-use std::{fs::read_to_string, path::PathBuf, str::from_utf8};
+use std::{collections::HashMap, fs::read_to_string, path::PathBuf, str::from_utf8};
 
 use log::debug;
+use tabled::builder::Builder;
 use rand::{seq::SliceRandom, Rng};
 use zstd::bulk::decompress;
 
 type BoxedError<'a> = Box<dyn std::error::Error + Send + Sync + 'a>;
 type StringVecResult<'a> = Result<Vec<String>, BoxedError<'a>>;
+type BytesResult<'a> = Result<Vec<u8>, BoxedError<'a>>;
+
+/// The value from which a pair of words that encodes two bytes, rather than three, is counted.
+const SHORT_PAIR_OFFSET: usize = 1 << 24;
 
 pub fn load_wordlist<'a, R: Rng + Sized>(path: &PathBuf, delimiter: &str, rng: &mut R) -> StringVecResult<'a> {
     let input = read_to_string(path)?;
@@ -27,23 +32,271 @@ pub fn load_wordlist<'a, R: Rng + Sized>(path: &PathBuf, delimiter: &str, rng: &
     Ok(wordlist)
 }
 
-pub fn load_default_wordlist<'a, R: Rng + Sized>(rng: &mut R) -> StringVecResult<'a> {
-    let buffer = include_bytes!("wordlist.txt.zst");
-    let bytes = decompress(buffer, 62144)?;
-    let mut wordlist = from_utf8(&bytes)?
+/// A wordlist which is embedded in the binary.
+pub struct BuiltinWordlist {
+    /// The name used to select the wordlist.
+    pub name: &'static str,
+    /// A short description of the wordlist.
+    pub description: &'static str,
+    /// The number of words in the wordlist.
+    pub words: usize,
+    buffer: &'static [u8],
+    size: usize
+}
+
+/// The name of the wordlist used when none is specified.
+pub const DEFAULT_WORDLIST: &str = "eff-large";
+
+/// Every wordlist embedded in the binary, each of which is stored in its canonical order.
+pub const BUILTIN_WORDLISTS: [BuiltinWordlist; 8] = [
+    BuiltinWordlist {
+        name: "eff-large",
+        description: "The EFF large wordlist for use with five dice",
+        words: 7776,
+        buffer: include_bytes!("wordlist.txt.zst"),
+        size: 62144
+    },
+    BuiltinWordlist {
+        name: "eff-short",
+        description: "The EFF short wordlist for use with four dice",
+        words: 1296,
+        buffer: include_bytes!("eff-short.txt.zst"),
+        size: 7180
+    },
+    BuiltinWordlist {
+        name: "bip39-english",
+        description: "The English BIP39 wordlist",
+        words: 2048,
+        buffer: include_bytes!("bip39-english.txt.zst"),
+        size: 13116
+    },
+    BuiltinWordlist {
+        name: "bip39-spanish",
+        description: "The Spanish BIP39 wordlist",
+        words: 2048,
+        buffer: include_bytes!("bip39-spanish.txt.zst"),
+        size: 13996
+    },
+    BuiltinWordlist {
+        name: "bip39-french",
+        description: "The French BIP39 wordlist",
+        words: 2048,
+        buffer: include_bytes!("bip39-french.txt.zst"),
+        size: 16777
+    },
+    BuiltinWordlist {
+        name: "bip39-italian",
+        description: "The Italian BIP39 wordlist",
+        words: 2048,
+        buffer: include_bytes!("bip39-italian.txt.zst"),
+        size: 16033
+    },
+    BuiltinWordlist {
+        name: "bip39-portuguese",
+        description: "The Portuguese BIP39 wordlist",
+        words: 2048,
+        buffer: include_bytes!("bip39-portuguese.txt.zst"),
+        size: 15671
+    },
+    BuiltinWordlist {
+        name: "bip39-czech",
+        description: "The Czech BIP39 wordlist",
+        words: 2048,
+        buffer: include_bytes!("bip39-czech.txt.zst"),
+        size: 14945
+    }
+];
+
+/// Loads a built-in wordlist by name in its canonical order, which the dice and mnemonic encodings depend on.
+pub fn load_builtin_wordlist<'a>(name: &str) -> StringVecResult<'a> {
+    let builtin = match BUILTIN_WORDLISTS.iter().find(|builtin| builtin.name == name) {
+        Some(builtin) => builtin,
+        None => return Err(format!("There is no built-in wordlist named {name}").into())
+    };
+
+    let bytes = decompress(builtin.buffer, builtin.size)?;
+    let wordlist = from_utf8(&bytes)?
         .split('\n')
         .map(|value| value.to_owned())
         .filter(|value| !value.is_empty())
         .collect::<Vec<_>>();
     let count = wordlist.len();
 
-    wordlist.sort();
-    wordlist.dedup();
-    wordlist.shuffle(rng);
+    assert_eq!(count, builtin.words);
 
-    assert_eq!(count, 7776);
+    debug!("Loaded {count} words from the {name} wordlist");
 
-    debug!("Loaded {count} words from the default wordlist");
+    Ok(wordlist)
+}
+
+/// Loads a built-in wordlist by name in a random order.
+pub fn load_named_wordlist<'a, R: Rng + Sized>(name: &str, rng: &mut R) -> StringVecResult<'a> {
+    let mut wordlist = load_builtin_wordlist(name)?;
+
+    wordlist.shuffle(rng);
 
     Ok(wordlist)
 }
+
+/// Loads the default wordlist in its canonical order, in which each word's position matches its dice index.
+pub fn load_canonical_wordlist<'a>() -> StringVecResult<'a> {
+    load_builtin_wordlist(DEFAULT_WORDLIST)
+}
+
+pub fn load_default_wordlist<'a, R: Rng + Sized>(rng: &mut R) -> StringVecResult<'a> {
+    load_named_wordlist(DEFAULT_WORDLIST, rng)
+}
+
+/// Loads the English BIP39 wordlist in its canonical order, which the mnemonic encoding depends on.
+pub fn load_bip39_wordlist<'a>() -> StringVecResult<'a> {
+    load_builtin_wordlist("bip39-english")
+}
+
+/// Encodes bytes as words from a wordlist of at least 4105 words, such as the canonical wordlist.
+///
+/// Every three bytes become a pair of words. One byte left over becomes a single word, and two bytes left over
+/// become a pair of words whose value lies beyond any that three bytes could take, so the encoding is reversible.
+pub fn encode_words(wordlist: &[String], bytes: &[u8]) -> String {
+    let base = wordlist.len();
+
+    assert!(base * base >= SHORT_PAIR_OFFSET + 65536, "The wordlist is too small to encode two bytes in a pair of words");
+
+    bytes.chunks(3)
+        .flat_map(|chunk| match *chunk {
+            [first, second, third] => {
+                let value = (first as usize) << 16 | (second as usize) << 8 | third as usize;
+
+                vec![value / base, value % base]
+            },
+            [first, second] => {
+                let value = SHORT_PAIR_OFFSET + ((first as usize) << 8 | second as usize);
+
+                vec![value / base, value % base]
+            },
+            _ => vec![chunk[0] as usize]
+        })
+        .map(|index| wordlist[index].as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Decodes words produced by [encode_words] back into bytes, ignoring case.
+pub fn decode_words<'a>(wordlist: &[String], text: &str) -> BytesResult<'a> {
+    let base = wordlist.len();
+    let indices = wordlist.iter()
+        .enumerate()
+        .map(|(index, word)| (word.as_str(), index))
+        .collect::<HashMap<_, _>>();
+    let lowercase = text.to_lowercase();
+    let words = lowercase.split_whitespace()
+        .enumerate()
+        .map(|(position, word)| match indices.get(word) {
+            Some(&index) => Ok(index),
+            None => Err(format!("Word {} ({word}) is not in the wordlist", position + 1))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let pairs = words.len().div_ceil(2);
+    let mut bytes = Vec::with_capacity(words.len() * 3 / 2);
+
+    for (position, pair) in words.chunks(2).enumerate() {
+        match *pair {
+            [first, second] => {
+                let value = first * base + second;
+
+                if value < SHORT_PAIR_OFFSET {
+                    bytes.extend_from_slice(&[(value >> 16) as u8, (value >> 8) as u8, value as u8]);
+                }
+                else if value < SHORT_PAIR_OFFSET + 65536 && position + 1 == pairs {
+                    bytes.extend_from_slice(&[((value - SHORT_PAIR_OFFSET) >> 8) as u8, value as u8]);
+                }
+                else {
+                    return Err(format!("Words {} and {} don't encode any bytes", position * 2 + 1, position * 2 + 2).into());
+                }
+            },
+            [single] if single < 256 => bytes.push(single as u8),
+            _ => return Err(format!("Word {} doesn't encode a byte", position * 2 + 1).into())
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Describes every built-in wordlist in a table, with the size of each list and the entropy of each word.
+pub fn describe_wordlists() -> String {
+    let mut builder = Builder::new();
+
+    builder.push_record(["Name", "Words", "Bits per word", "Description"]);
+
+    for builtin in BUILTIN_WORDLISTS {
+        let name = match builtin.name == DEFAULT_WORDLIST {
+            true => format!("{} (default)", builtin.name),
+            false => builtin.name.to_string()
+        };
+
+        builder.push_record([
+            name,
+            builtin.words.to_string(),
+            format!("{:.2}", (builtin.words as f64).log2()),
+            builtin.description.to_string()
+        ]);
+    }
+
+    builder.build().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn loads_every_builtin_wordlist() {
+        let sizes = [
+            ("eff-large", 7776),
+            ("eff-short", 1296),
+            ("bip39-english", 2048),
+            ("bip39-spanish", 2048),
+            ("bip39-french", 2048),
+            ("bip39-italian", 2048),
+            ("bip39-portuguese", 2048),
+            ("bip39-czech", 2048)
+        ];
+
+        assert_eq!(BUILTIN_WORDLISTS.map(|builtin| (builtin.name, builtin.words)), sizes);
+
+        for builtin in BUILTIN_WORDLISTS {
+            let wordlist = load_builtin_wordlist(builtin.name).unwrap();
+            let unique = wordlist.iter().collect::<HashSet<_>>();
+
+            assert_eq!(unique.len(), builtin.words, "{}", builtin.name);
+        }
+
+        let short = load_builtin_wordlist("eff-short").unwrap();
+
+        assert_eq!((short[0].as_str(), short[1295].as_str()), ("acid", "zoom"));
+        assert!(short.iter().all(|word| word.len() <= 5));
+    }
+
+    #[test]
+    fn encodes_bytes_as_words_reversibly() {
+        let wordlist = load_canonical_wordlist().unwrap();
+
+        for length in 0..10 {
+            let bytes = (0..length).map(|value| (value * 97 + 255) as u8).collect::<Vec<_>>();
+            let words = encode_words(&wordlist, &bytes);
+
+            assert_eq!(words.split_whitespace().count(), length / 3 * 2 + [0, 1, 2][length % 3]);
+            assert_eq!(decode_words(&wordlist, &words.to_uppercase()).unwrap(), bytes);
+        }
+
+        assert_eq!(encode_words(&wordlist, &[0, 0, 0]), "abacus abacus");
+        assert!(decode_words(&wordlist, "zoom zoom abacus").is_err());
+        assert!(decode_words(&wordlist, "abacus klingon").is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_wordlist() {
+        assert!(load_builtin_wordlist("klingon").is_err());
+    }
+}